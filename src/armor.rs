@@ -0,0 +1,593 @@
+//! OpenPGP ASCII armor, as specified in RFC 4880 section 6
+//!
+//! This reads and writes armored blocks such as the signature of a signed
+//! file, or a key that an `Encryption` field points to.
+
+// https://tools.ietf.org/html/rfc4880#section-6
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+const CRC24_INIT: u32 = 0x00B7_04CE;
+const CRC24_POLY: u32 = 0x0186_4CFB;
+
+/// The CRC-24 checksum of the given data
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = CRC24_INIT;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= CRC24_POLY;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Signifies that base64 could not be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Error {
+    /// A character outside the base64 alphabet, at the given byte offset
+    InvalidCharacter { character: char, offset: usize },
+    /// Padding followed by more data, at the byte offset of the padding
+    UnexpectedPadding { offset: usize },
+    /// The data is not a whole number of base64 groups
+    InvalidLength,
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter { character, .. } => {
+                write!(f, "invalid base64 character {:?}", character)
+            }
+            Self::UnexpectedPadding { .. } => write!(f, "unexpected padding in base64"),
+            Self::InvalidLength => write!(f, "invalid base64 length"),
+        }
+    }
+}
+
+impl Error for Base64Error {}
+
+/// Encode base64, with padding
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut string = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let mut group = [0; 4];
+        group[1..=chunk.len()].copy_from_slice(chunk);
+        let group = u32::from_be_bytes(group);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let value = (group >> (18 - 6 * i)) & 0x3F;
+                string.push(char::from(BASE64_ALPHABET[value as usize]));
+            } else {
+                string.push('=');
+            }
+        }
+    }
+    string
+}
+
+fn base64_value(c: char) -> Option<u32> {
+    let c = u8::try_from(c).ok()?;
+    Some(u32::from(match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    }))
+}
+
+/// Decode padded base64, ignoring whitespace
+pub fn decode_base64(string: &str) -> Result<Vec<u8>, Base64Error> {
+    let mut values = Vec::with_capacity(string.len());
+    let mut padding = None;
+    let mut padding_len = 0;
+    for (offset, character) in string.char_indices() {
+        if character.is_ascii_whitespace() {
+            continue;
+        }
+        if character == '=' {
+            padding.get_or_insert(offset);
+            padding_len += 1;
+            continue;
+        }
+        if let Some(offset) = padding {
+            return Err(Base64Error::UnexpectedPadding { offset });
+        }
+        let value =
+            base64_value(character).ok_or(Base64Error::InvalidCharacter { character, offset })?;
+        values.push(value);
+    }
+    if values.len() % 4 == 1 || padding_len != (4 - values.len() % 4) % 4 {
+        return Err(Base64Error::InvalidLength);
+    }
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let mut group = 0;
+        for (i, value) in chunk.iter().enumerate() {
+            group |= value << (18 - 6 * i);
+        }
+        let group = group.to_be_bytes();
+        bytes.extend_from_slice(&group[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+/// What an armored block contains, as named by its header line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Message,
+    PublicKey,
+    PrivateKey,
+    /// A part of a message split into several blocks, numbered from 1, and
+    /// the number of parts if known
+    MessagePart {
+        part: u32,
+        total: Option<u32>,
+    },
+    Signature,
+}
+
+impl fmt::Display for Kind {
+    /// The label of the header and tail lines, such as `PUBLIC KEY BLOCK`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Message => write!(f, "MESSAGE"),
+            Self::PublicKey => write!(f, "PUBLIC KEY BLOCK"),
+            Self::PrivateKey => write!(f, "PRIVATE KEY BLOCK"),
+            Self::MessagePart { part, total: None } => write!(f, "MESSAGE, PART {}", part),
+            Self::MessagePart {
+                part,
+                total: Some(total),
+            } => write!(f, "MESSAGE, PART {}/{}", part, total),
+            Self::Signature => write!(f, "SIGNATURE"),
+        }
+    }
+}
+
+impl Kind {
+    fn from_label(label: &str) -> Option<Self> {
+        Some(match label {
+            "MESSAGE" => Self::Message,
+            "PUBLIC KEY BLOCK" => Self::PublicKey,
+            "PRIVATE KEY BLOCK" => Self::PrivateKey,
+            "SIGNATURE" => Self::Signature,
+            _ => {
+                let part = label.strip_prefix("MESSAGE, PART ")?;
+                let number = |n: &str| n.parse().ok().filter(|&n| n > 0);
+                match part.split_once('/') {
+                    Some((part, total)) => Self::MessagePart {
+                        part: number(part)?,
+                        total: Some(number(total)?),
+                    },
+                    None => Self::MessagePart {
+                        part: number(part)?,
+                        total: None,
+                    },
+                }
+            }
+        })
+    }
+}
+
+/// Signifies that an armored block is malformed
+///
+/// Line numbers start at 1, and count from the first line of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArmorError {
+    /// The input does not start with a `-----BEGIN PGP ...-----` line
+    MissingHeaderLine,
+    /// The header line names a kind of block that is not known
+    UnknownKind { line: usize, label: String },
+    /// An armor header is not a `Key: Value` pair
+    InvalidArmorHeader { line: usize },
+    /// The armor headers are not followed by an empty line
+    MissingEmptyLine,
+    /// The base64 data is malformed
+    InvalidBase64 { line: usize, error: Base64Error },
+    /// The checksum line is not four base64 characters
+    InvalidChecksum { line: usize },
+    /// The checksum does not match the data
+    ChecksumMismatch {
+        line: usize,
+        expected: u32,
+        actual: u32,
+    },
+    /// Data follows the checksum line
+    DataAfterChecksum { line: usize },
+    /// The tail line does not match the header line
+    MismatchedTailLine { line: usize },
+    /// There is no `-----END PGP ...-----` line
+    MissingTailLine,
+    /// Something other than whitespace follows the tail line
+    TrailingContent { line: usize },
+}
+
+impl ArmorError {
+    /// The line the error is on, if any
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            Self::UnknownKind { line, .. }
+            | Self::InvalidArmorHeader { line }
+            | Self::InvalidBase64 { line, .. }
+            | Self::InvalidChecksum { line }
+            | Self::ChecksumMismatch { line, .. }
+            | Self::DataAfterChecksum { line }
+            | Self::MismatchedTailLine { line }
+            | Self::TrailingContent { line } => Some(line),
+            Self::MissingHeaderLine | Self::MissingEmptyLine | Self::MissingTailLine => None,
+        }
+    }
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingHeaderLine => write!(f, "missing armor header line"),
+            Self::UnknownKind { label, .. } => write!(f, "unknown armor block {:?}", label),
+            Self::InvalidArmorHeader { .. } => write!(f, "invalid armor header"),
+            Self::MissingEmptyLine => write!(f, "missing empty line after armor headers"),
+            Self::InvalidBase64 { error, .. } => write!(f, "{}", error),
+            Self::InvalidChecksum { .. } => write!(f, "invalid armor checksum"),
+            Self::ChecksumMismatch {
+                expected, actual, ..
+            } => write!(
+                f,
+                "armor checksum mismatch: expected {:06X}, computed {:06X}",
+                expected, actual
+            ),
+            Self::DataAfterChecksum { .. } => write!(f, "armor data after checksum"),
+            Self::MismatchedTailLine { .. } => {
+                write!(f, "armor tail line does not match the header line")
+            }
+            Self::MissingTailLine => write!(f, "missing armor tail line"),
+            Self::TrailingContent { .. } => write!(f, "unexpected content after armor"),
+        }?;
+        match self.line() {
+            Some(line) => write!(f, " on line {}", line),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for ArmorError {}
+
+/// An ASCII-armored block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Armor {
+    pub kind: Kind,
+    /// The armor headers, such as `Comment`, in order
+    pub headers: Vec<(String, String)>,
+    pub data: Vec<u8>,
+}
+
+impl Armor {
+    pub fn new(kind: Kind, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            kind,
+            headers: Vec::new(),
+            data: data.into(),
+        }
+    }
+}
+
+/// The label of a `-----BEGIN PGP ...-----` or `-----END PGP ...-----` line
+fn label<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    line.strip_prefix(prefix)?.strip_suffix("-----")
+}
+
+impl FromStr for Armor {
+    type Err = ArmorError;
+    /// Decode an armored block, verifying its checksum if present
+    ///
+    /// Empty lines may come before the header line and after the tail line.
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut lines = string
+            .lines()
+            .map(str::trim_end)
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .skip_while(|(_, line)| line.is_empty());
+
+        let (kind, begin) = match lines.next() {
+            Some((number, line)) => {
+                let begin = label(line, "-----BEGIN PGP ").ok_or(ArmorError::MissingHeaderLine)?;
+                let kind = Kind::from_label(begin).ok_or_else(|| ArmorError::UnknownKind {
+                    line: number,
+                    label: begin.into(),
+                })?;
+                (kind, begin)
+            }
+            None => return Err(ArmorError::MissingHeaderLine),
+        };
+
+        // Armor headers, terminated by an empty line
+        let mut headers = Vec::new();
+        loop {
+            match lines.next() {
+                Some((_, "")) => break,
+                Some((number, line)) => match line.split_once(": ") {
+                    Some((key, value)) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+                        headers.push((key.into(), value.into()));
+                    }
+                    _ => return Err(ArmorError::InvalidArmorHeader { line: number }),
+                },
+                None => return Err(ArmorError::MissingEmptyLine),
+            }
+        }
+
+        // The body, with the line number and offset of each line in it
+        let mut body = String::new();
+        let mut body_lines = Vec::new();
+        let mut checksum = None;
+        for (number, line) in lines.by_ref() {
+            if let Some(end) = label(line, "-----END PGP ") {
+                if end != begin {
+                    return Err(ArmorError::MismatchedTailLine { line: number });
+                }
+                let data = decode_base64(&body).map_err(|error| {
+                    let offset = match error {
+                        Base64Error::InvalidCharacter { offset, .. }
+                        | Base64Error::UnexpectedPadding { offset } => offset,
+                        Base64Error::InvalidLength => body.len(),
+                    };
+                    let line = body_lines
+                        .iter()
+                        .rev()
+                        .find(|&&(_, start)| start <= offset)
+                        .map_or(number, |&(line, _)| line);
+                    ArmorError::InvalidBase64 { line, error }
+                })?;
+                if let Some((line, expected)) = checksum {
+                    let actual = crc24(&data);
+                    if actual != expected {
+                        return Err(ArmorError::ChecksumMismatch {
+                            line,
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+                if let Some((number, _)) = lines.find(|(_, line)| !line.is_empty()) {
+                    return Err(ArmorError::TrailingContent { line: number });
+                }
+                return Ok(Self {
+                    kind,
+                    headers,
+                    data,
+                });
+            } else if checksum.is_some() {
+                return Err(ArmorError::DataAfterChecksum { line: number });
+            } else if let Some(encoded) = line.strip_prefix('=') {
+                let bytes = match decode_base64(encoded) {
+                    Ok(bytes) if bytes.len() == 3 && encoded.len() == 4 => bytes,
+                    _ => return Err(ArmorError::InvalidChecksum { line: number }),
+                };
+                checksum = Some((
+                    number,
+                    u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]),
+                ));
+            } else {
+                body_lines.push((number, body.len()));
+                body.push_str(line);
+            }
+        }
+        Err(ArmorError::MissingTailLine)
+    }
+}
+
+/// Writes the block with a checksum and LF line endings, without a line ending
+/// after the tail line
+impl fmt::Display for Armor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "-----BEGIN PGP {}-----", self.kind)?;
+        for (key, value) in &self.headers {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        writeln!(f)?;
+        let body = encode_base64(&self.data);
+        // Lines of 64 characters, as GnuPG writes them
+        for line in body.as_bytes().chunks(64) {
+            writeln!(f, "{}", std::str::from_utf8(line).expect("base64 is ASCII"))?;
+        }
+        let checksum = crc24(&self.data).to_be_bytes();
+        writeln!(f, "={}", encode_base64(&checksum[1..]))?;
+        write!(f, "-----END PGP {}-----", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64() {
+        assert_eq!(decode_base64("").unwrap(), b"");
+        assert_eq!(decode_base64("Zg==").unwrap(), b"f");
+        assert_eq!(decode_base64("Zm8=").unwrap(), b"fo");
+        assert_eq!(decode_base64("Zm9v").unwrap(), b"foo");
+        assert_eq!(decode_base64("Zm9v\r\nYmFy").unwrap(), b"foobar");
+        assert_eq!(decode_base64("Zm9"), Err(Base64Error::InvalidLength));
+        assert_eq!(decode_base64("Zg="), Err(Base64Error::InvalidLength));
+        assert_eq!(decode_base64("===="), Err(Base64Error::InvalidLength));
+        assert_eq!(
+            decode_base64("Zm=v"),
+            Err(Base64Error::UnexpectedPadding { offset: 2 })
+        );
+        assert_eq!(
+            decode_base64("Zm9é"),
+            Err(Base64Error::InvalidCharacter {
+                character: 'é',
+                offset: 3
+            })
+        );
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", &[0xFF; 100]] {
+            assert_eq!(decode_base64(&encode_base64(data)).unwrap(), data);
+        }
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+    }
+
+    #[test]
+    fn checksum() {
+        let armored = "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n=T8JV\n-----END PGP SIGNATURE-----\n";
+        assert_eq!(crc24(b"foo"), 0x4FC255);
+        let armor: Armor = armored.parse().unwrap();
+        assert_eq!(armor, Armor::new(Kind::Signature, *b"foo"));
+        assert_eq!(
+            armored.replace("Zm9v", "Zm9w").parse::<Armor>(),
+            Err(ArmorError::ChecksumMismatch {
+                line: 4,
+                expected: 0x4FC255,
+                actual: crc24(b"fop"),
+            })
+        );
+        assert!(armored.replace("\n=T8JV", "").parse::<Armor>().is_ok());
+        assert_eq!(armor.to_string() + "\n", armored);
+
+        let data: Vec<u8> = (0..=255).collect();
+        let armored = Armor::new(Kind::Message, data.clone()).to_string();
+        assert!(armored.lines().all(|line| line.len() <= 64));
+        assert_eq!(armored.parse::<Armor>().unwrap().data, data);
+    }
+
+    #[test]
+    fn empty_body() {
+        let armor = Armor::new(Kind::Signature, Vec::new());
+        let armored = armor.to_string();
+        assert_eq!(
+            armored,
+            "-----BEGIN PGP SIGNATURE-----\n\n=twTO\n-----END PGP SIGNATURE-----"
+        );
+        assert_eq!(armored.parse::<Armor>(), Ok(armor));
+        assert_eq!(
+            armored.replace("=twTO", "=AAAA").parse::<Armor>(),
+            Err(ArmorError::ChecksumMismatch {
+                line: 3,
+                expected: 0,
+                actual: crc24(b""),
+            })
+        );
+    }
+
+    #[test]
+    fn headers() {
+        let armored = "\r\n-----BEGIN PGP PUBLIC KEY BLOCK-----\r\n\
+                       Comment: Example Security\r\n\
+                       Comment: <security@example.com>\r\n\
+                       \r\n\
+                       Zm9v\r\n\
+                       YmFy\r\n\
+                       -----END PGP PUBLIC KEY BLOCK-----\r\n\r\n";
+        let armor: Armor = armored.parse().unwrap();
+        assert_eq!(armor.kind, Kind::PublicKey);
+        assert_eq!(
+            armor.headers,
+            vec![
+                ("Comment".into(), "Example Security".into()),
+                ("Comment".into(), "<security@example.com>".into()),
+            ]
+        );
+        assert_eq!(armor.data, b"foobar");
+        assert_eq!(armor.to_string().parse::<Armor>().unwrap(), armor);
+
+        let parts = [
+            (
+                "MESSAGE, PART 2",
+                Kind::MessagePart {
+                    part: 2,
+                    total: None,
+                },
+            ),
+            (
+                "MESSAGE, PART 2/3",
+                Kind::MessagePart {
+                    part: 2,
+                    total: Some(3),
+                },
+            ),
+            ("PRIVATE KEY BLOCK", Kind::PrivateKey),
+        ];
+        for (label, kind) in parts.iter() {
+            assert_eq!(Kind::from_label(label), Some(*kind));
+            assert_eq!(kind.to_string(), *label);
+        }
+        assert_eq!(Kind::from_label("MESSAGE, PART 0"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("", ArmorError::MissingHeaderLine),
+            ("Zm9v", ArmorError::MissingHeaderLine),
+            (
+                "-----BEGIN PGP SIGNED MESSAGE-----\n\nZm9v\n-----END PGP SIGNED MESSAGE-----",
+                ArmorError::UnknownKind {
+                    line: 1,
+                    label: "SIGNED MESSAGE".into(),
+                },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\nComment\n\nZm9v\n-----END PGP SIGNATURE-----",
+                ArmorError::InvalidArmorHeader { line: 2 },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\nComment: a",
+                ArmorError::MissingEmptyLine,
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\nZm*v\n-----END PGP SIGNATURE-----",
+                ArmorError::InvalidBase64 {
+                    line: 4,
+                    error: Base64Error::InvalidCharacter {
+                        character: '*',
+                        offset: 6,
+                    },
+                },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\nZm9\n-----END PGP SIGNATURE-----",
+                ArmorError::InvalidBase64 {
+                    line: 4,
+                    error: Base64Error::InvalidLength,
+                },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n=T8J\n-----END PGP SIGNATURE-----",
+                ArmorError::InvalidChecksum { line: 4 },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n=T8JV\nZm9v\n-----END PGP SIGNATURE-----",
+                ArmorError::DataAfterChecksum { line: 5 },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n-----END PGP MESSAGE-----",
+                ArmorError::MismatchedTailLine { line: 4 },
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n",
+                ArmorError::MissingTailLine,
+            ),
+            (
+                "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n-----END PGP SIGNATURE-----\n\nZm9v",
+                ArmorError::TrailingContent { line: 6 },
+            ),
+        ];
+        for (armored, error) in cases.iter() {
+            assert_eq!(armored.parse::<Armor>().as_ref(), Err(error), "{}", armored);
+        }
+        assert_eq!(
+            cases[5].1.to_string(),
+            "invalid base64 character '*' on line 4"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, HashSet};
+
+/// The arguments of a subcommand, split into positional arguments,
+/// `--name value` options and `--name` flags
+pub struct Args {
+    pub positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    /// Parse arguments, taking the given names as flags without a value
+    pub fn parse(args: impl IntoIterator<Item = String>, flags: &[&str]) -> Result<Self, String> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix("--") {
+                Some(name) => name,
+                None => {
+                    parsed.positional.push(arg);
+                    continue;
+                }
+            };
+            if flags.contains(&name) {
+                if !parsed.flags.insert(name.to_string()) {
+                    return Err(format!("--{} given more than once", name));
+                }
+                continue;
+            }
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for --{}", name))?;
+                    (name.to_string(), value)
+                }
+            };
+            if parsed.options.insert(name.clone(), value).is_some() {
+                return Err(format!("--{} given more than once", name));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Take the value of an option
+    pub fn option(&mut self, name: &str) -> Option<String> {
+        self.options.remove(name)
+    }
+
+    /// Take a flag, returning whether it was given
+    pub fn flag(&mut self, name: &str) -> bool {
+        self.flags.remove(name)
+    }
+
+    /// Check that every option was taken
+    pub fn finish(self) -> Result<Vec<String>, String> {
+        match self.options.keys().next() {
+            Some(name) => Err(format!("unknown option --{}", name)),
+            None => Ok(self.positional),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! The configuration file of `generate`, made of `key = value` lines
+//!
+//! Blank lines and lines starting with `#` are ignored. The keys are `spec`,
+//! `header`, `contact`, `expires`, `expires-in-days`, `acknowledgments`,
+//! `canonical`, `encryption`, `hiring`, `policy`, `preferred-languages` and
+//! `extension` (as `Name: value`). `header` and `contact` may be repeated.
+
+use crate::parse_spec;
+use chrono::DateTime;
+use security_txt::{SecurityTxt, SecurityTxtBuilder, WriteOptions};
+use std::time::Duration;
+use url::Url;
+
+/// Read a configuration into a builder, and the options to write the file with
+pub fn parse(config: &str) -> Result<(SecurityTxtBuilder, WriteOptions), String> {
+    let mut builder = SecurityTxt::builder();
+    let mut header: Vec<&str> = Vec::new();
+    for (i, line) in config.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: &str| format!("line {}: {}", i + 1, message);
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| error("expected `key = value`"))?;
+        builder = match key {
+            "spec" => builder.spec(parse_spec(Some(value.into())).map_err(|e| error(&e))?),
+            "header" => {
+                header.push(value);
+                builder
+            }
+            "contact" if Url::parse(value).is_ok() => builder.contact_url(value),
+            "contact" if value.starts_with('+') => builder.contact_phone(value),
+            "contact" => builder.contact_email(value),
+            "expires" => builder
+                .expires(DateTime::parse_from_rfc3339(value).map_err(|e| error(&e.to_string()))?),
+            "expires-in-days" => {
+                let seconds = value
+                    .parse::<u64>()
+                    .map_err(|_| error("expected a number of days"))?
+                    .checked_mul(24 * 60 * 60)
+                    .ok_or_else(|| error("too many days"))?;
+                builder.expires_in(Duration::from_secs(seconds))
+            }
+            "acknowledgments" => builder.acknowledgments(value),
+            "canonical" => builder.canonical(value),
+            "encryption" => builder.encryption(value),
+            "hiring" => builder.hiring(value),
+            "policy" => builder.policy(value),
+            "preferred-languages" => builder.preferred_languages(value.split(',').map(str::trim)),
+            "extension" => {
+                let (name, value) = value
+                    .split_once(':')
+                    .ok_or_else(|| error("expected `extension = Name: value`"))?;
+                builder.extension(name.trim(), value.trim())
+            }
+            key => return Err(error(&format!("unknown key {:?}", key))),
+        };
+    }
+    let options = WriteOptions {
+        header: if header.is_empty() {
+            None
+        } else {
+            Some(header.join("\n"))
+        },
+        ..WriteOptions::default()
+    };
+    Ok((builder, options))
+}
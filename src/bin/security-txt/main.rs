@@ -0,0 +1,341 @@
+mod args;
+mod config;
+mod report;
+mod scan;
+
+use args::Args;
+use report::{Format, Report};
+use scan::ScanFormat;
+use security_txt::{
+    discover_with, scan_each, validate, DiscoveryOptions, Document, PgpSigner, RenewPolicy,
+    ScanOptions, SecurityTxt, Severity, SpecVersion, UreqFetcher, ValidationOptions,
+};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: security-txt <command> [options]
+
+Commands:
+  lint <file|->        Parse and validate a file, or standard input
+      --format <human|json>
+      --spec <rfc9116|draft-09>
+      --url <url>      The URL the file was retrieved from
+      --now <time>     The RFC 3339 time to check Expires against, instead
+                       of the current time
+  fetch <domain>       Discover, parse and validate the file of a domain
+      --format <human|json>
+      --spec <rfc9116|draft-09>
+  scan <file|->        Fetch the files of many domains, listed one per line,
+                       and print a line for each domain as it is done
+      --format <jsonl|csv>
+      --spec <rfc9116|draft-09>
+      --concurrency <n>  How many domains to scan at once, 8 by default
+      --interval <ms>  The minimum time between requests to the same host,
+                       1000 by default
+  generate             Write a file from a configuration
+      --config <file>  See below
+      --output <file>  Defaults to standard output
+      --key <file>     Sign the file with an ASCII-armored OpenPGP secret key
+  renew <file|->       Push the Expires field of a file forward
+      --days <n>       How far from now, 180 by default and capped below a year
+      --force          Renew the file even if it has already expired
+      --output <file>  Defaults to the input file, or standard output for -
+      --key <file>     Sign the file again, which signed files require
+
+Configuration:
+  One `key = value` per line, with the keys spec, header, contact, expires,
+  expires-in-days, acknowledgments, canonical, encryption, hiring, policy,
+  preferred-languages and extension (as `Name: value`).
+  Lines starting with # are ignored, and header and contact may be repeated.
+  The header is left out of signed files.
+
+Keys:
+  A key protected by a passphrase is unlocked with the passphrase in the
+  SECURITY_TXT_PASSPHRASE environment variable.
+
+Exit status of lint and fetch:
+  0  no problems, or only informational ones
+  1  warnings
+  2  errors
+  3  the command could not be run
+
+The scan command exits with 0 once every domain has been scanned, and the
+renew command with 0 when the file was renewed and 2 when it was refused.
+";
+
+const EXIT_FAILURE: i32 = 3;
+
+pub fn parse_spec(spec: Option<String>) -> Result<SpecVersion, String> {
+    match spec.as_deref() {
+        None | Some("rfc9116") => Ok(SpecVersion::Rfc9116),
+        Some("draft-09") => Ok(SpecVersion::Draft09),
+        Some(spec) => Err(format!("unknown specification {:?}", spec)),
+    }
+}
+
+fn exit_code(severity: Option<Severity>) -> i32 {
+    match severity {
+        None | Some(Severity::Info) => 0,
+        Some(Severity::Warning) => 1,
+        Some(Severity::Error) => 2,
+    }
+}
+
+fn single(positional: Vec<String>, what: &str) -> Result<String, String> {
+    let mut positional = positional.into_iter();
+    match (positional.next(), positional.next()) {
+        (Some(argument), None) => Ok(argument),
+        _ => Err(format!("expected exactly one {}", what)),
+    }
+}
+
+fn lint(mut args: Args) -> Result<i32, String> {
+    let format = Format::parse(args.option("format"))?;
+    let spec = parse_spec(args.option("spec"))?;
+    let url = match args.option("url") {
+        Some(url) => Some(url.parse().map_err(|e| format!("invalid URL: {}", e))?),
+        None => None,
+    };
+    let now = match args.option("now") {
+        Some(now) => Some(
+            chrono::DateTime::parse_from_rfc3339(&now)
+                .map_err(|e| format!("invalid time {:?}: {}", now, e))?
+                .to_utc(),
+        ),
+        None => None,
+    };
+    let path = single(args.finish()?, "file")?;
+
+    let input = read_input(&path)?;
+    let (security_txt, diagnostics) = SecurityTxt::parse_with_diagnostics(&input, spec);
+    let mut options = ValidationOptions {
+        spec,
+        url,
+        ..ValidationOptions::default()
+    };
+    if let Some(now) = now {
+        options.now = now;
+    }
+    let report = Report {
+        source: if path == "-" { "<stdin>".into() } else { path },
+        diagnostics,
+        findings: validate(&security_txt, &options),
+    };
+    report.print(format);
+    Ok(exit_code(report.severity()))
+}
+
+fn fetch(mut args: Args) -> Result<i32, String> {
+    let format = Format::parse(args.option("format"))?;
+    let spec = parse_spec(args.option("spec"))?;
+    let domain = single(args.finish()?, "domain")?;
+
+    let options = DiscoveryOptions {
+        spec,
+        ..DiscoveryOptions::default()
+    };
+    let discovery = match discover_with(&domain, &options, &UreqFetcher::new()) {
+        Ok(discovery) => discovery,
+        Err(error) => {
+            match format {
+                Format::Human => println!("{}: error: {}", domain, error),
+                Format::Json => println!(
+                    "{{\"source\":{},\"severity\":\"error\",\"error\":{}}}",
+                    report::json_string(&domain),
+                    report::json_string(&error.to_string())
+                ),
+            }
+            return Ok(exit_code(Some(Severity::Error)));
+        }
+    };
+    let options = ValidationOptions {
+        spec,
+        ..ValidationOptions::default()
+    };
+    let mut findings = discovery.findings;
+    findings.extend(validate(&discovery.security_txt, &options));
+    let report = Report {
+        source: discovery.url.to_string(),
+        diagnostics: discovery.diagnostics,
+        findings,
+    };
+    report.print(format);
+    Ok(exit_code(report.severity()))
+}
+
+fn number(value: Option<String>, name: &str, default: u64) -> Result<u64, String> {
+    match value {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid --{} {:?}", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("could not read standard input: {}", e))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))
+    }
+}
+
+fn scan(mut args: Args) -> Result<i32, String> {
+    let format = ScanFormat::parse(args.option("format"))?;
+    let spec = parse_spec(args.option("spec"))?;
+    let concurrency = number(args.option("concurrency"), "concurrency", 8)?;
+    let interval = number(args.option("interval"), "interval", 1000)?;
+    let path = single(args.finish()?, "file")?;
+    if concurrency == 0 {
+        return Err("--concurrency must be at least 1".into());
+    }
+
+    let input = read_input(&path)?;
+    let domains: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let options = ScanOptions {
+        discovery: DiscoveryOptions {
+            spec,
+            ..DiscoveryOptions::default()
+        },
+        concurrency: concurrency as usize,
+        host_interval: Duration::from_millis(interval),
+    };
+    let mut stdout = io::stdout().lock();
+    let mut result = match format.header() {
+        Some(header) => writeln!(stdout, "{}", header),
+        None => Ok(()),
+    };
+    scan_each(&domains, &options, &UreqFetcher::new(), |_, row| {
+        if result.is_ok() {
+            result = writeln!(stdout, "{}", format.row(&row)).and_then(|_| stdout.flush());
+        }
+    });
+    result.map_err(|e| format!("could not write the results: {}", e))?;
+    Ok(0)
+}
+
+/// Load the secret key given with `--key`, if any
+fn signer(key: Option<String>) -> Result<Option<PgpSigner>, String> {
+    let path = match key {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let signer =
+        PgpSigner::from_file(&path).map_err(|e| format!("could not load {}: {}", path, e))?;
+    Ok(Some(match std::env::var("SECURITY_TXT_PASSPHRASE") {
+        Ok(passphrase) => signer.with_passphrase(&passphrase),
+        Err(_) => signer,
+    }))
+}
+
+fn generate(mut args: Args) -> Result<i32, String> {
+    let config = args
+        .option("config")
+        .ok_or_else(|| "missing --config".to_string())?;
+    let output = args.option("output");
+    let signer = signer(args.option("key"))?;
+    if !args.finish()?.is_empty() {
+        return Err("generate takes no arguments".into());
+    }
+
+    let config =
+        fs::read_to_string(&config).map_err(|e| format!("could not read {}: {}", config, e))?;
+    let (builder, options) = config::parse(&config).map_err(|e| format!("config {}", e))?;
+    let security_txt = match builder.build() {
+        Ok(security_txt) => security_txt,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return Ok(exit_code(Some(Severity::Error)));
+        }
+    };
+    let security_txt = match &signer {
+        Some(signer) => security_txt.sign(signer).map_err(|e| e.to_string())?,
+        None => security_txt,
+    };
+    let result = match &output {
+        Some(path) => {
+            fs::File::create(path).and_then(|file| security_txt.write_with(file, &options))
+        }
+        None => security_txt.write_with(io::stdout().lock(), &options),
+    };
+    result.map_err(|e| format!("could not write the file: {}", e))?;
+    Ok(0)
+}
+
+fn renew(mut args: Args) -> Result<i32, String> {
+    let days = number(args.option("days"), "days", 180)?;
+    let force = args.flag("force");
+    let output = args.option("output");
+    let signer = signer(args.option("key"))?;
+    let path = single(args.finish()?, "file")?;
+
+    let input = read_input(&path)?;
+    let mut document = Document::parse(&input);
+    let policy = RenewPolicy {
+        window: chrono::Duration::days(days.min(365) as i64),
+        force,
+        ..RenewPolicy::default()
+    };
+    let source = if path == "-" { "<stdin>" } else { &path };
+    let renewed = match &signer {
+        Some(signer) => security_txt::renew_signed(&mut document, &policy, signer),
+        None => security_txt::renew(&mut document, &policy),
+    };
+    match renewed {
+        Ok(expires) => eprintln!("{}: Expires set to {}", source, expires.to_rfc3339()),
+        Err(error) => {
+            eprintln!("{}: error: {}", source, error);
+            return Ok(2);
+        }
+    }
+    let output = output.unwrap_or(path);
+    let result = if output == "-" {
+        io::stdout()
+            .lock()
+            .write_all(document.to_string().as_bytes())
+    } else {
+        fs::write(&output, document.to_string())
+    };
+    result.map_err(|e| format!("could not write the file: {}", e))?;
+    Ok(0)
+}
+
+fn run() -> Result<i32, String> {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    match command.as_deref() {
+        Some("lint") => lint(Args::parse(args, &[])?),
+        Some("fetch") => fetch(Args::parse(args, &[])?),
+        Some("scan") => scan(Args::parse(args, &[])?),
+        Some("generate") => generate(Args::parse(args, &[])?),
+        Some("renew") => renew(Args::parse(args, &["force"])?),
+        Some("help") | Some("--help") | Some("-h") => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+        Some(command) => Err(format!("unknown command {:?}", command)),
+        None => Err("missing command".into()),
+    }
+}
+
+fn main() {
+    let code = match run() {
+        Ok(code) => code,
+        Err(message) => {
+            let _ = writeln!(io::stderr(), "security-txt: {}\n\n{}", message, USAGE);
+            EXIT_FAILURE
+        }
+    };
+    process::exit(code);
+}
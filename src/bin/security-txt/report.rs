@@ -0,0 +1,141 @@
+use security_txt::{Diagnostic, Finding, Severity};
+use std::fmt::Write;
+
+/// How to print reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+impl Format {
+    pub fn parse(format: Option<String>) -> Result<Self, String> {
+        match format.as_deref() {
+            None | Some("human") => Ok(Self::Human),
+            Some("json") => Ok(Self::Json),
+            Some(format) => Err(format!("unknown format {:?}", format)),
+        }
+    }
+}
+
+/// Quote a string as a JSON string
+pub fn json_string(string: &str) -> String {
+    let mut output = String::with_capacity(string.len() + 2);
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// A JSON value, or `null`
+pub fn json_option(value: Option<impl ToString>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "null".into(),
+    }
+}
+
+/// The most serious severity of all problems found
+pub(crate) fn severity(diagnostics: &[Diagnostic], findings: &[Finding]) -> Option<Severity> {
+    let diagnostics = diagnostics.iter().map(|diagnostic| diagnostic.severity);
+    let findings = findings.iter().map(|finding| finding.severity);
+    diagnostics.chain(findings).max()
+}
+
+/// Everything found about one file
+pub struct Report {
+    /// The path or URL the file was read from
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// The most serious severity of all problems found
+    pub fn severity(&self) -> Option<Severity> {
+        severity(&self.diagnostics, &self.findings)
+    }
+
+    pub fn human(&self) -> String {
+        let mut output = String::new();
+        for diagnostic in &self.diagnostics {
+            writeln!(output, "{}: {}", self.source, diagnostic).unwrap();
+        }
+        for finding in &self.findings {
+            writeln!(output, "{}: {}", self.source, finding).unwrap();
+        }
+        if output.is_empty() {
+            writeln!(output, "{}: no problems found", self.source).unwrap();
+        }
+        output
+    }
+
+    pub fn json(&self) -> String {
+        let diagnostics: Vec<_> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let error = &diagnostic.error;
+                format!(
+                    "{{\"severity\":{},\"kind\":{},\"line\":{},\"span\":[{},{}],\
+                     \"field\":{},\"message\":{}}}",
+                    json_string(&diagnostic.severity.to_string()),
+                    json_string(error.kind().id()),
+                    error.line(),
+                    error.span().start,
+                    error.span().end,
+                    json_option(error.field().map(json_string)),
+                    json_string(&message(&error.kind().to_string(), error.detail())),
+                )
+            })
+            .collect();
+        let findings: Vec<_> = self
+            .findings
+            .iter()
+            .map(|finding| {
+                format!(
+                    "{{\"severity\":{},\"rule\":{},\"field\":{},\"message\":{}}}",
+                    json_string(&finding.severity.to_string()),
+                    json_string(finding.rule.id()),
+                    json_option(finding.field),
+                    json_string(&finding.message),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"source\":{},\"severity\":{},\"diagnostics\":[{}],\"findings\":[{}]}}\n",
+            json_string(&self.source),
+            json_option(
+                self.severity()
+                    .map(|severity| json_string(&severity.to_string()))
+            ),
+            diagnostics.join(","),
+            findings.join(","),
+        )
+    }
+
+    pub fn print(&self, format: Format) {
+        match format {
+            Format::Human => print!("{}", self.human()),
+            Format::Json => print!("{}", self.json()),
+        }
+    }
+}
+
+fn message(kind: &str, detail: &str) -> String {
+    if detail.is_empty() {
+        kind.into()
+    } else {
+        format!("{}: {}", kind, detail)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::report::{json_option, json_string, severity};
+use security_txt::{Location, ScanRow};
+
+/// How to print scan results, one line per domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanFormat {
+    Jsonl,
+    Csv,
+}
+
+impl ScanFormat {
+    pub fn parse(format: Option<String>) -> Result<Self, String> {
+        match format.as_deref() {
+            None | Some("jsonl") => Ok(Self::Jsonl),
+            Some("csv") => Ok(Self::Csv),
+            Some(format) => Err(format!("unknown format {:?}", format)),
+        }
+    }
+
+    /// The line printed before all rows, if any
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            Self::Jsonl => None,
+            Self::Csv => Some(
+                "domain,found,url,location,signed,expires,contacts,severity,diagnostics,\
+                 findings,error",
+            ),
+        }
+    }
+
+    pub fn row(self, row: &ScanRow) -> String {
+        match self {
+            Self::Jsonl => jsonl(row),
+            Self::Csv => csv(row),
+        }
+    }
+}
+
+fn location(location: Location) -> &'static str {
+    match location {
+        Location::WellKnown => "well-known",
+        Location::Legacy => "legacy",
+    }
+}
+
+fn jsonl(row: &ScanRow) -> String {
+    let findings: Vec<_> = row
+        .findings
+        .iter()
+        .map(|finding| {
+            format!(
+                "{{\"severity\":{},\"rule\":{},\"message\":{}}}",
+                json_string(&finding.severity.to_string()),
+                json_string(finding.rule.id()),
+                json_string(&finding.message),
+            )
+        })
+        .collect();
+    format!(
+        "{{\"domain\":{},\"found\":{},\"url\":{},\"location\":{},\"signed\":{},\
+         \"expires\":{},\"contacts\":{},\"severity\":{},\"diagnostics\":{},\
+         \"findings\":[{}],\"error\":{}}}",
+        json_string(&row.domain),
+        row.found(),
+        json_option(row.url.as_ref().map(|url| json_string(url.as_str()))),
+        json_option(row.location.map(|l| json_string(location(l)))),
+        row.signed,
+        json_option(
+            row.expires
+                .map(|expires| json_string(&expires.to_rfc3339()))
+        ),
+        row.contacts,
+        json_option(
+            severity(&row.diagnostics, &row.findings)
+                .map(|severity| json_string(&severity.to_string()))
+        ),
+        row.diagnostics.len(),
+        findings.join(","),
+        json_option(row.error.as_ref().map(|e| json_string(&e.to_string()))),
+    )
+}
+
+/// Quote a CSV field if it needs to be
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+fn csv(row: &ScanRow) -> String {
+    let rules: Vec<_> = row
+        .findings
+        .iter()
+        .map(|finding| finding.rule.id())
+        .collect();
+    let fields = [
+        row.domain.clone(),
+        row.found().to_string(),
+        row.url
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+        row.location.map(location).unwrap_or_default().into(),
+        row.signed.to_string(),
+        row.expires
+            .map(|expires| expires.to_rfc3339())
+            .unwrap_or_default(),
+        row.contacts.to_string(),
+        severity(&row.diagnostics, &row.findings)
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        row.diagnostics.len().to_string(),
+        rules.join(";"),
+        row.error
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+    ];
+    let fields: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+    fields.join(",")
+}
@@ -0,0 +1,350 @@
+use crate::{check_extension, ContactUri, ExtensionError, Field, SecurityTxt, SpecVersion};
+use chrono::prelude::*;
+use core::str::FromStr;
+use language_tags::LanguageTag;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+use url::Url;
+
+/// Signifies that a `SecurityTxtBuilder` could not build a valid file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A value passed to the builder could not be used for the field
+    InvalidValue { field: &'static str, detail: String },
+    /// An extension field could not be added
+    InvalidExtension(ExtensionError),
+    /// No `Contact` field was added
+    MissingContact,
+    /// No `Expires` field was set, but the specification requires one
+    MissingExpires,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidValue { field, detail } => {
+                write!(f, "invalid value for {} field: {}", field, detail)
+            }
+            Self::InvalidExtension(error) => write!(f, "{}", error),
+            Self::MissingContact => write!(f, "at least one Contact field is required"),
+            Self::MissingExpires => write!(f, "an Expires field is required"),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// Builds a `SecurityTxt` that follows the rules of the specification
+///
+/// Values are checked as they are added, but errors are only reported by
+/// `build`, so that calls can be chained.
+#[derive(Debug, Default)]
+pub struct SecurityTxtBuilder {
+    spec: SpecVersion,
+    fields: Vec<Field>,
+    errors: Vec<BuildError>,
+}
+
+fn invalid(field: &'static str, detail: impl ToString) -> BuildError {
+    BuildError::InvalidValue {
+        field,
+        detail: detail.to_string(),
+    }
+}
+
+impl SecurityTxtBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The version of the specification whose rules `build` enforces
+    pub fn spec(mut self, spec: SpecVersion) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    fn push(mut self, field: Result<Field, BuildError>) -> Self {
+        match field {
+            Ok(field) => self.fields.push(field),
+            Err(error) => self.errors.push(error),
+        }
+        self
+    }
+
+    /// Add a field that may only appear once, replacing any previous value
+    fn set(mut self, field: Result<Field, BuildError>) -> Self {
+        let field = match field {
+            Ok(field) => field,
+            Err(error) => return self.push(Err(error)),
+        };
+        match self
+            .fields
+            .iter_mut()
+            .find(|other| mem::discriminant(*other) == mem::discriminant(&field))
+        {
+            Some(other) => *other = field,
+            None => self.fields.push(field),
+        }
+        self
+    }
+
+    fn url(field: &'static str, url: &str) -> Result<Url, BuildError> {
+        Url::parse(url).map_err(|error| invalid(field, error))
+    }
+
+    /// Add a `Contact` field with a `mailto:` URI
+    pub fn contact_email(self, email: &str) -> Self {
+        self.push(
+            ContactUri::email(email)
+                .map(Field::Contact)
+                .map_err(|error| invalid("Contact", error.detail())),
+        )
+    }
+
+    /// Add a `Contact` field with a `tel:` URI, from a number in global
+    /// format such as `+44 5555 555 555`
+    pub fn contact_phone(self, number: &str) -> Self {
+        self.push(
+            ContactUri::phone(number)
+                .map(Field::Contact)
+                .map_err(|error| invalid("Contact", error.detail())),
+        )
+    }
+
+    /// Add a `Contact` field with a URI
+    pub fn contact_url(self, url: &str) -> Self {
+        let contact = Self::url("Contact", url).and_then(|url| {
+            ContactUri::from_url(url).map_err(|error| invalid("Contact", error.detail()))
+        });
+        self.push(contact.map(Field::Contact))
+    }
+
+    /// Set the `Expires` field
+    pub fn expires(self, datetime: DateTime<FixedOffset>) -> Self {
+        self.set(Ok(Field::Expires(datetime)))
+    }
+
+    /// Set the `Expires` field to the given duration from now, rounded down
+    /// to whole seconds
+    pub fn expires_in(self, duration: Duration) -> Self {
+        let expires = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|duration| Utc::now().checked_add_signed(duration))
+            .and_then(|datetime| datetime.with_nanosecond(0))
+            .map(|datetime| Field::Expires(datetime.fixed_offset()))
+            .ok_or_else(|| invalid("Expires", "duration is out of range"));
+        self.set(expires)
+    }
+
+    /// Add an `Acknowledgments` field
+    pub fn acknowledgments(self, url: &str) -> Self {
+        self.push(Self::url("Acknowledgments", url).map(Field::Acknowledgments))
+    }
+
+    /// Add a `Canonical` field
+    pub fn canonical(self, url: &str) -> Self {
+        self.push(Self::url("Canonical", url).map(Field::Canonical))
+    }
+
+    /// Add an `Encryption` field
+    pub fn encryption(self, url: &str) -> Self {
+        self.push(Self::url("Encryption", url).map(Field::Encryption))
+    }
+
+    /// Add a `Hiring` field
+    pub fn hiring(self, url: &str) -> Self {
+        self.push(Self::url("Hiring", url).map(Field::Hiring))
+    }
+
+    /// Add a `Policy` field
+    pub fn policy(self, url: &str) -> Self {
+        self.push(Self::url("Policy", url).map(Field::Policy))
+    }
+
+    /// Set the `Preferred-Languages` field
+    pub fn preferred_languages<'a>(self, languages: impl IntoIterator<Item = &'a str>) -> Self {
+        let languages = languages
+            .into_iter()
+            .map(LanguageTag::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| invalid("Preferred-Languages", error))
+            .and_then(|languages| {
+                if languages.is_empty() {
+                    Err(invalid("Preferred-Languages", "no languages given"))
+                } else {
+                    Ok(Field::PreferredLanguages(languages))
+                }
+            });
+        self.set(languages)
+    }
+
+    /// Add an extension field
+    ///
+    /// Fields of the specification must be added with their own methods.
+    pub fn extension(self, name: &str, value: &str) -> Self {
+        self.push(
+            check_extension(name, value)
+                .map(|()| Field::Extension(name.into(), value.into()))
+                .map_err(BuildError::InvalidExtension),
+        )
+    }
+
+    /// Check the rules of the specification, and build the file
+    pub fn build(self) -> Result<SecurityTxt, BuildError> {
+        if let Some(error) = self.errors.into_iter().next() {
+            return Err(error);
+        }
+        if !self
+            .fields
+            .iter()
+            .any(|field| matches!(field, Field::Contact(_)))
+        {
+            return Err(BuildError::MissingContact);
+        }
+        if self.spec.requires_expires()
+            && !self
+                .fields
+                .iter()
+                .any(|field| matches!(field, Field::Expires(_)))
+        {
+            return Err(BuildError::MissingExpires);
+        }
+        Ok(SecurityTxt::Unsigned(self.fields))
+    }
+}
+
+impl SecurityTxt {
+    pub fn builder() -> SecurityTxtBuilder {
+        SecurityTxtBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let security_txt = SecurityTxt::builder()
+            .contact_email("security@example.com")
+            .contact_phone("+44 5555 555 555")
+            .contact_url("https://example.com/security")
+            .expires_in(Duration::from_secs(60 * 60 * 24 * 7))
+            .preferred_languages(vec!["en", "da"])
+            .canonical("https://example.com/.well-known/security.txt")
+            .extension("CSAF", "https://example.com/provider-metadata.json")
+            .expires(DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            security_txt.to_string(),
+            "Contact: mailto:security@example.com\n\
+             Contact: tel:+44-5555-555-555\n\
+             Contact: https://example.com/security\n\
+             Expires: 2030-01-01T00:00:00Z\n\
+             Preferred-Languages: en, da\n\
+             Canonical: https://example.com/.well-known/security.txt\n\
+             CSAF: https://example.com/provider-metadata.json\n"
+        );
+    }
+
+    #[test]
+    fn reserved_characters_in_email() {
+        for address in ["a#b@example.com", "a?b@example.com", "a%41@example.com"].iter() {
+            let security_txt = SecurityTxt::builder()
+                .contact_email(address)
+                .expires(DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z").unwrap())
+                .build()
+                .unwrap();
+            assert_eq!(crate::parse(&security_txt.to_string()), Ok(security_txt));
+        }
+    }
+
+    #[test]
+    fn expires_in() {
+        let security_txt = SecurityTxt::builder()
+            .contact_email("security@example.com")
+            .expires_in(Duration::from_secs(60 * 60))
+            .build()
+            .unwrap();
+        match &security_txt.fields()[1] {
+            Field::Expires(expires) => {
+                let remaining = expires.signed_duration_since(Utc::now());
+                assert!(remaining <= chrono::Duration::hours(1));
+                assert!(remaining > chrono::Duration::minutes(59));
+            }
+            field => panic!("unexpected field {:?}", field),
+        }
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(
+            SecurityTxt::builder()
+                .expires_in(Duration::from_secs(60))
+                .build(),
+            Err(BuildError::MissingContact)
+        );
+        assert_eq!(
+            SecurityTxt::builder()
+                .contact_email("security@example.com")
+                .build(),
+            Err(BuildError::MissingExpires)
+        );
+        assert!(SecurityTxt::builder()
+            .spec(SpecVersion::Draft09)
+            .contact_email("security@example.com")
+            .build()
+            .is_ok());
+
+        let invalid = |builder: SecurityTxtBuilder| match builder.build() {
+            Err(BuildError::InvalidValue { field, .. }) => field,
+            result => panic!("unexpected result {:?}", result),
+        };
+        assert_eq!(
+            invalid(SecurityTxt::builder().contact_email("invalid")),
+            "Contact"
+        );
+        assert_eq!(
+            invalid(SecurityTxt::builder().contact_phone("5555")),
+            "Contact"
+        );
+        assert_eq!(invalid(SecurityTxt::builder().policy("/policy")), "Policy");
+        assert_eq!(
+            invalid(SecurityTxt::builder().preferred_languages(vec!["1"])),
+            "Preferred-Languages"
+        );
+    }
+
+    #[test]
+    fn invalid_extensions() {
+        let extension = |name: &str, value: &str| {
+            SecurityTxt::builder()
+                .contact_email("security@example.com")
+                .expires_in(Duration::from_secs(60))
+                .extension(name, value)
+                .build()
+        };
+        assert!(extension("CSAF", "https://example.com/provider-metadata.json").is_ok());
+        let cases = [
+            ("", "value"),
+            ("Expires", "2030-01-01T00:00:00Z"),
+            ("contact", "mailto:a@b.com"),
+            ("Acknowledgements", "https://example.com/thanks"),
+            ("CSAF:", "value"),
+            ("My Field", "value"),
+            ("CSAF\nContact", "value"),
+            ("#CSAF", "value"),
+            ("CSAF", "value\nContact: https://evil.example"),
+            ("CSAF", "value\r"),
+        ];
+        for (name, value) in cases.iter() {
+            match extension(name, value) {
+                Err(BuildError::InvalidExtension(error)) => assert_eq!(error.name, *name),
+                result => panic!("unexpected result {:?} for {:?}", result, name),
+            }
+        }
+    }
+}
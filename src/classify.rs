@@ -0,0 +1,104 @@
+use crate::{Field, SecurityTxt, SpecVersion};
+use std::fmt;
+
+/// Why a response body is not a security.txt file at all, such as a page
+/// served in its place by a server that does not return 404
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NotSecurityTxt {
+    /// The body is an HTML (or other markup) document
+    HtmlDetected,
+    /// The body is a JSON document
+    JsonDetected,
+    /// The body was served with a media type other than `text/plain`, and
+    /// has no recognised fields
+    ContentTypeMismatch(String),
+    /// The body has no recognised fields
+    NoFields,
+}
+
+impl fmt::Display for NotSecurityTxt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::HtmlDetected => write!(f, "the response is an HTML document"),
+            Self::JsonDetected => write!(f, "the response is a JSON document"),
+            Self::ContentTypeMismatch(content_type) => {
+                write!(f, "the response was served as {:?}", content_type)
+            }
+            Self::NoFields => write!(f, "the response has no security.txt fields"),
+        }
+    }
+}
+
+/// Decide whether a body that was parsed leniently into `security_txt` is a
+/// security.txt file
+pub(crate) fn check(
+    body: &str,
+    content_type: Option<&str>,
+    security_txt: &SecurityTxt,
+) -> Result<(), NotSecurityTxt> {
+    let start = body.trim_start_matches(|c: char| c == '\u{feff}' || c.is_whitespace());
+    if start.starts_with('<') {
+        return Err(NotSecurityTxt::HtmlDetected);
+    }
+    if start.starts_with('{') || start.starts_with('[') {
+        return Err(NotSecurityTxt::JsonDetected);
+    }
+    if security_txt
+        .fields()
+        .iter()
+        .all(|field| matches!(field, Field::Extension(..)))
+    {
+        return Err(match content_type {
+            Some(content_type)
+                if !content_type
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .eq_ignore_ascii_case("text/plain") =>
+            {
+                NotSecurityTxt::ContentTypeMismatch(content_type.into())
+            }
+            _ => NotSecurityTxt::NoFields,
+        });
+    }
+    Ok(())
+}
+
+/// Decide whether a response body, served with the given `Content-Type`, is
+/// a security.txt file at all
+pub fn classify(body: &str, content_type: Option<&str>) -> Result<(), NotSecurityTxt> {
+    // The most permissive version, since only whether fields are recognised matters
+    let (security_txt, _) = SecurityTxt::parse_with_diagnostics(body, SpecVersion::Draft09);
+    check(body, content_type, &security_txt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reasons() {
+        let plain = Some("text/plain; charset=utf-8");
+        let contact = "Contact: mailto:a@b.com\n";
+        assert_eq!(classify(contact, plain), Ok(()));
+        assert_eq!(classify(contact, Some("text/html")), Ok(()));
+        assert_eq!(classify(contact, None), Ok(()));
+        assert_eq!(
+            classify("\u{feff}\n<!DOCTYPE html>\n<p>Not found</p>", plain),
+            Err(NotSecurityTxt::HtmlDetected)
+        );
+        assert_eq!(
+            classify("{\"error\": \"not found\"}", None),
+            Err(NotSecurityTxt::JsonDetected)
+        );
+        assert_eq!(
+            classify("Not Found", Some("application/octet-stream")),
+            Err(NotSecurityTxt::ContentTypeMismatch(
+                "application/octet-stream".into()
+            ))
+        );
+        assert_eq!(classify("Not Found", plain), Err(NotSecurityTxt::NoFields));
+        assert_eq!(classify("", None), Err(NotSecurityTxt::NoFields));
+    }
+}
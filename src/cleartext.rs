@@ -0,0 +1,276 @@
+// https://tools.ietf.org/html/rfc4880#section-7
+
+use crate::{lines, ErrorKind, ParseError};
+
+pub(crate) const BEGIN_SIGNED_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+pub(crate) const BEGIN_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+pub(crate) const END_SIGNATURE: &str = "-----END PGP SIGNATURE-----";
+
+/// The hash algorithms that may be named in the `Hash` armor header
+const HASH_ALGORITHMS: &[&str] = &[
+    "MD5",
+    "SHA1",
+    "RIPEMD160",
+    "SHA256",
+    "SHA384",
+    "SHA512",
+    "SHA224",
+];
+
+/// The parts of an OpenPGP cleartext signed message
+#[derive(Debug)]
+pub(crate) struct Cleartext<'a> {
+    /// The hash algorithms named in the `Hash` armor headers
+    pub hashes: Vec<&'a str>,
+    /// The dash-unescaped lines of the signed text, with their line number
+    /// and byte offset in the input
+    pub lines: Vec<(usize, usize, &'a str)>,
+    /// The dash-unescaped signed text, without the line ending preceding the
+    /// signature
+    pub text: String,
+    /// The armored signature, from its header line to its tail line
+    pub signature: Option<&'a str>,
+    /// Problems with the framing of the message
+    pub errors: Vec<ParseError>,
+}
+
+/// Whether the input starts like a cleartext signed message
+pub(crate) fn is_signed(string: &str) -> bool {
+    lines(string)
+        .next()
+        .is_some_and(|(_, _, line)| line.trim_end() == BEGIN_SIGNED_MESSAGE)
+}
+
+fn invalid(number: usize, offset: usize, line: &str, detail: &str) -> ParseError {
+    ParseError::new(ErrorKind::InvalidSignature, 0..line.len())
+        .at_line(number, offset)
+        .with_detail(detail)
+}
+
+/// Split a cleartext signed message into its parts
+///
+/// This never fails; problems with the framing are collected into
+/// `Cleartext::errors`, and as much of the message as possible is returned.
+pub(crate) fn split(string: &str) -> Cleartext<'_> {
+    let mut lines = lines(string).skip(1).peekable();
+    let mut cleartext = Cleartext {
+        hashes: Vec::new(),
+        lines: Vec::new(),
+        text: String::new(),
+        signature: None,
+        errors: Vec::new(),
+    };
+
+    // Armor headers, terminated by an empty line
+    let mut previous = (1, 0, BEGIN_SIGNED_MESSAGE);
+    loop {
+        let (number, offset, line) = match lines.next() {
+            Some(line) => line,
+            None => {
+                let (number, offset, line) = previous;
+                let error = invalid(
+                    number,
+                    offset,
+                    line,
+                    "missing empty line after armor headers",
+                );
+                cleartext.errors.push(error);
+                return cleartext;
+            }
+        };
+        previous = (number, offset, line);
+        if line.trim_end().is_empty() {
+            break;
+        }
+        match line.split_once(": ") {
+            Some(("Hash", hashes)) => {
+                for hash in hashes.split(',').map(str::trim) {
+                    if !HASH_ALGORITHMS.contains(&hash) {
+                        let error = invalid(number, offset, line, "unknown hash algorithm");
+                        cleartext.errors.push(error);
+                    }
+                    cleartext.hashes.push(hash);
+                }
+            }
+            _ => {
+                let error = invalid(number, offset, line, "unexpected armor header");
+                cleartext.errors.push(error);
+            }
+        }
+    }
+
+    // The dash-escaped text
+    let text_start = lines.peek().map_or(string.len(), |&(_, offset, _)| offset);
+    let mut text_end = string.len();
+    for (number, offset, line) in &mut lines {
+        if line.trim_end() == BEGIN_SIGNATURE {
+            text_end = offset;
+            previous = (number, offset, line);
+            break;
+        }
+        if let Some(unescaped) = line.strip_prefix("- ") {
+            cleartext.lines.push((number, offset + 2, unescaped));
+        } else {
+            if line.starts_with('-') {
+                let error = invalid(number, offset, line, "line is not dash-escaped");
+                cleartext.errors.push(error);
+            }
+            cleartext.lines.push((number, offset, line));
+        }
+    }
+    for line in string[text_start..text_end].split_inclusive('\n') {
+        cleartext
+            .text
+            .push_str(line.strip_prefix("- ").unwrap_or(line));
+    }
+    if text_end < string.len() {
+        // The line ending before the signature is not part of the signed text
+        let text = &mut cleartext.text;
+        text.truncate(text.strip_suffix('\n').unwrap_or(text).len());
+        text.truncate(text.strip_suffix('\r').unwrap_or(text).len());
+    } else {
+        let (number, offset, line) = cleartext.lines.last().copied().unwrap_or((1, 0, ""));
+        let error = invalid(number, offset, line, "missing signature");
+        cleartext.errors.push(error);
+        return cleartext;
+    }
+
+    // The armored signature, optionally followed by empty lines
+    let mut signature_end = None;
+    for (number, offset, line) in &mut lines {
+        if signature_end.is_none() {
+            if line.trim_end() == END_SIGNATURE {
+                signature_end = Some(offset + line.len());
+            }
+        } else if !line.trim().is_empty() {
+            let detail = "unexpected content after signature";
+            cleartext.errors.push(invalid(number, offset, line, detail));
+            break;
+        }
+    }
+    match signature_end {
+        Some(end) => cleartext.signature = Some(&string[text_end..end]),
+        None => {
+            let (number, offset, line) = previous;
+            let error = invalid(number, offset, line, "unterminated signature");
+            cleartext.errors.push(error);
+        }
+    }
+    cleartext
+}
+
+/// Canonicalise signed text as specified in RFC 4880 section 7.1: trailing
+/// whitespace is removed from each line, and lines are separated by CRLF
+pub(crate) fn canonicalize(text: &str) -> String {
+    text.split('\n')
+        .map(|line| line.trim_end_matches([' ', '\t', '\r']))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// Dash-escape a line of the signed text
+pub(crate) fn dash_escape(line: &str) -> String {
+    if line.starts_with('-') {
+        format!("- {}", line)
+    } else {
+        line.into()
+    }
+}
+
+/// The name of the hash algorithm used by the first signature packet, for use
+/// in the `Hash` armor header
+pub(crate) fn hash_algorithm(packets: &[u8]) -> Option<&'static str> {
+    // https://tools.ietf.org/html/rfc4880#section-4.2
+    let tag = *packets.first()?;
+    let header_len = if tag & 0x40 != 0 {
+        match *packets.get(1)? {
+            0..=191 => 2,
+            192..=223 => 3,
+            255 => 6,
+            _ => return None,
+        }
+    } else {
+        match tag & 0x03 {
+            0 => 2,
+            1 => 3,
+            2 => 5,
+            _ => 1,
+        }
+    };
+    // https://tools.ietf.org/html/rfc4880#section-5.2
+    let body = packets.get(header_len..)?;
+    let algorithm = match body.first()? {
+        3 => body.get(16)?,
+        _ => body.get(3)?,
+    };
+    // https://tools.ietf.org/html/rfc4880#section-9.4
+    Some(match algorithm {
+        1 => "MD5",
+        2 => "SHA1",
+        3 => "RIPEMD160",
+        8 => "SHA256",
+        9 => "SHA384",
+        10 => "SHA512",
+        11 => "SHA224",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNATURE: &str =
+        "-----BEGIN PGP SIGNATURE-----\n\nabc\n=def\n-----END PGP SIGNATURE-----";
+
+    #[test]
+    fn dash_escaping() {
+        let message = format!(
+            "{}\r\nHash: SHA256, SHA512\r\n\r\nContact: mailto:a@b.com\r\n- -Dashed\r\n- Policy: https://b.com\r\n{}\r\n",
+            BEGIN_SIGNED_MESSAGE, SIGNATURE
+        );
+        let cleartext = split(&message);
+        assert!(cleartext.errors.is_empty(), "{:?}", cleartext.errors);
+        assert_eq!(cleartext.hashes, vec!["SHA256", "SHA512"]);
+        assert_eq!(
+            cleartext.text,
+            "Contact: mailto:a@b.com\r\n-Dashed\r\nPolicy: https://b.com"
+        );
+        let lines: Vec<_> = cleartext.lines.iter().map(|&(n, _, l)| (n, l)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (4, "Contact: mailto:a@b.com"),
+                (5, "-Dashed"),
+                (6, "Policy: https://b.com"),
+            ]
+        );
+        assert_eq!(
+            &message[cleartext.lines[2].1..][..6],
+            "Policy",
+            "offsets point into the input"
+        );
+        assert_eq!(cleartext.signature, Some(SIGNATURE));
+    }
+
+    #[test]
+    fn framing_errors() {
+        let cases = [
+            format!("{}\nHash: SHA256\n", BEGIN_SIGNED_MESSAGE),
+            format!("{}\nHash: FOO\n\nA: b\n{}", BEGIN_SIGNED_MESSAGE, SIGNATURE),
+            format!(
+                "{}\nComment: a\n\nA: b\n{}",
+                BEGIN_SIGNED_MESSAGE, SIGNATURE
+            ),
+            format!("{}\n\n-A: b\n{}", BEGIN_SIGNED_MESSAGE, SIGNATURE),
+            format!("{}\n\nA: b\n", BEGIN_SIGNED_MESSAGE),
+            format!("{}\n\nA: b\n{}", BEGIN_SIGNED_MESSAGE, BEGIN_SIGNATURE),
+            format!("{}\n\nA: b\n{}\nA: b", BEGIN_SIGNED_MESSAGE, SIGNATURE),
+        ];
+        for case in cases.iter() {
+            let errors = split(case).errors;
+            assert_eq!(errors.len(), 1, "{}", case);
+            assert_eq!(errors[0].kind(), ErrorKind::InvalidSignature);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// https://www.rfc-editor.org/rfc/rfc9116#section-2.5.3
+// https://tools.ietf.org/html/rfc5322#section-3.4.1
+// https://tools.ietf.org/html/rfc3966#section-3
+// https://tools.ietf.org/html/rfc6068#section-2
+
+use crate::{parse_url, ErrorKind, ParseError};
+use core::str::FromStr;
+use std::fmt;
+use url::Url;
+
+/// The URI of a `Contact` field, classified by how it is used
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContactUri {
+    /// A `mailto:` URI
+    Email(Url),
+    /// A `tel:` URI
+    Phone(Url),
+    /// An `https:` (or insecure `http:`) URI
+    Web(Url),
+    /// Any other URI
+    Other(Url),
+}
+
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c)
+}
+
+fn is_dot_atom(string: &str) -> bool {
+    string
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+fn is_quoted_string(string: &str) -> bool {
+    let inner = match string
+        .strip_prefix('"')
+        .and_then(|string| string.strip_suffix('"'))
+    {
+        Some(inner) => inner,
+        None => return false,
+    };
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if c == ' ' || c == '\t' || c.is_ascii_graphic() => {}
+                _ => return false,
+            },
+            '"' => return false,
+            c if c == ' ' || c == '\t' || c.is_ascii_graphic() => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn is_domain_literal(string: &str) -> bool {
+    string
+        .strip_prefix('[')
+        .and_then(|string| string.strip_suffix(']'))
+        .is_some_and(|inner| {
+            inner
+                .chars()
+                .all(|c| c.is_ascii_graphic() && !"[]\\".contains(c))
+        })
+}
+
+/// Whether the string is an `addr-spec` as specified in RFC 5322
+fn is_email_address(string: &str) -> bool {
+    match string.rfind('@') {
+        Some(at) => {
+            let (local, domain) = (&string[..at], &string[at + 1..]);
+            (is_dot_atom(local) || is_quoted_string(local))
+                && (is_dot_atom(domain) || is_domain_literal(domain))
+        }
+        None => false,
+    }
+}
+
+fn percent_decode(string: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(string.len());
+    let mut iter = string.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Percent-encode every character that may not appear as is in the address
+/// of a `mailto:` URI
+fn percent_encode(string: &str) -> String {
+    let mut encoded = String::with_capacity(string.len());
+    for byte in string.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~!$'()*+;:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Encode an email address for a `mailto:` URI, keeping the brackets of a
+/// domain literal
+fn encode_email_address(address: &str) -> String {
+    let at = address.rfind('@').expect("the address was checked");
+    let (local, domain) = (&address[..at], &address[at + 1..]);
+    let domain = match domain
+        .strip_prefix('[')
+        .and_then(|domain| domain.strip_suffix(']'))
+    {
+        Some(literal) => format!("[{}]", percent_encode(literal)),
+        None => percent_encode(domain),
+    };
+    format!("{}@{}", percent_encode(local), domain)
+}
+
+fn is_visual_separator(c: char) -> bool {
+    "-.()".contains(c)
+}
+
+/// Whether the string is a `telephone-subscriber` as specified in RFC 3966
+fn is_telephone_subscriber(string: &str) -> bool {
+    let mut parts = string.split(';');
+    let number = parts.next().unwrap_or_default();
+    let mut params = parts.map(|param| param.split('=').next().unwrap_or_default());
+    if let Some(digits) = number.strip_prefix('+') {
+        digits.chars().any(|c| c.is_ascii_digit())
+            && digits
+                .chars()
+                .all(|c| c.is_ascii_digit() || is_visual_separator(c))
+    } else {
+        number
+            .chars()
+            .any(|c| c.is_ascii_hexdigit() || c == '*' || c == '#')
+            && number
+                .chars()
+                .all(|c| c.is_ascii_hexdigit() || c == '*' || c == '#' || is_visual_separator(c))
+            && params.any(|name| name.eq_ignore_ascii_case("phone-context"))
+    }
+}
+
+fn invalid(kind: ErrorKind, string: &str, detail: &str) -> ParseError {
+    ParseError::new(kind, 0..string.len()).with_detail(detail)
+}
+
+impl ContactUri {
+    /// Classify a URI, checking that email addresses and telephone numbers
+    /// are well-formed
+    pub fn from_url(url: Url) -> Result<Self, ParseError> {
+        let error = |kind, detail| invalid(kind, url.as_str(), detail);
+        match url.scheme() {
+            "mailto" => {
+                // Commas within an address are percent-encoded, so addresses
+                // are separated before decoding
+                let addresses = url
+                    .path()
+                    .split(',')
+                    .map(percent_decode)
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| error(ErrorKind::InvalidUrl, "invalid percent-encoding"))?;
+                if addresses
+                    .iter()
+                    .all(|address| is_email_address(address.trim()))
+                {
+                    Ok(Self::Email(url))
+                } else {
+                    Err(error(ErrorKind::InvalidContact, "invalid email address"))
+                }
+            }
+            "tel" => {
+                if is_telephone_subscriber(url.path()) {
+                    Ok(Self::Phone(url))
+                } else {
+                    Err(error(ErrorKind::InvalidContact, "invalid telephone number"))
+                }
+            }
+            "https" | "http" => Ok(Self::Web(url)),
+            _ => Ok(Self::Other(url)),
+        }
+    }
+
+    /// Create a `mailto:` URI from an email address
+    pub fn email(address: &str) -> Result<Self, ParseError> {
+        let error = || invalid(ErrorKind::InvalidContact, address, "invalid email address");
+        if !is_email_address(address) {
+            return Err(error());
+        }
+        Url::parse(&format!("mailto:{}", encode_email_address(address)))
+            .map(Self::Email)
+            .map_err(|_| error())
+    }
+
+    /// Create a `tel:` URI from a global telephone number, replacing
+    /// whitespace with `-` separators
+    pub fn phone(number: &str) -> Result<Self, ParseError> {
+        let error = |detail| invalid(ErrorKind::InvalidContact, number, detail);
+        let global = number.split_whitespace().collect::<Vec<_>>().join("-");
+        if !global.starts_with('+') || !is_telephone_subscriber(&global) {
+            return Err(error("telephone numbers must be in global format"));
+        }
+        Url::parse(&format!("tel:{}", global))
+            .map(Self::Phone)
+            .map_err(|_| error("invalid telephone number"))
+    }
+
+    /// Recover a contact that was written as a bare email address or
+    /// telephone number instead of a URI
+    pub fn recover(value: &str) -> Option<Self> {
+        Self::email(value).or_else(|_| Self::phone(value)).ok()
+    }
+
+    pub fn as_url(&self) -> &Url {
+        match self {
+            Self::Email(url) | Self::Phone(url) | Self::Web(url) | Self::Other(url) => url,
+        }
+    }
+
+    pub fn into_url(self) -> Url {
+        match self {
+            Self::Email(url) | Self::Phone(url) | Self::Web(url) | Self::Other(url) => url,
+        }
+    }
+}
+
+impl FromStr for ContactUri {
+    type Err = ParseError;
+    /// Parse and classify a URI
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::from_url(parse_url(string)?)
+            .map_err(|error| invalid(error.kind(), string, error.detail()))
+    }
+}
+
+impl fmt::Display for ContactUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_url())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(string: &str) -> Result<ContactUri, ParseError> {
+        ContactUri::from_url(Url::parse(string).unwrap())
+    }
+
+    #[test]
+    fn email_addresses() {
+        let valid = [
+            "security@example.com",
+            "first.last+tag@sub.example.com",
+            "!#$%&'*+-/=?^_`{|}~@example.com",
+            "\"quoted \\\" string\"@example.com",
+            "user@[192.168.0.1]",
+        ];
+        for address in valid.iter() {
+            assert!(is_email_address(address), "{}", address);
+        }
+        let invalid = [
+            "",
+            "example.com",
+            "@example.com",
+            "user@",
+            "first..last@example.com",
+            ".user@example.com",
+            "us er@example.com",
+            "user@exa mple.com",
+            "\"unterminated@example.com",
+        ];
+        for address in invalid.iter() {
+            assert!(!is_email_address(address), "{}", address);
+        }
+        let encoded = [
+            ("security@example.com", "mailto:security@example.com"),
+            ("a#b@example.com", "mailto:a%23b@example.com"),
+            ("a?b@example.com", "mailto:a%3Fb@example.com"),
+            ("a%41@example.com", "mailto:a%2541@example.com"),
+            ("a/b=c&d@example.com", "mailto:a%2Fb%3Dc%26d@example.com"),
+            ("\"a b\"@example.com", "mailto:%22a%20b%22@example.com"),
+            ("\"a,b\"@example.com", "mailto:%22a%2Cb%22@example.com"),
+            ("user@[192.168.0.1]", "mailto:user@[192.168.0.1]"),
+        ];
+        for (address, uri) in encoded.iter() {
+            let contact = ContactUri::email(address).unwrap();
+            assert_eq!(contact.to_string(), *uri);
+            assert_eq!(ContactUri::from_str(uri), Ok(contact));
+        }
+    }
+
+    #[test]
+    fn classification() {
+        assert!(matches!(
+            classify("mailto:a@b.com"),
+            Ok(ContactUri::Email(_))
+        ));
+        let error = classify("mailto:a@b.com,c%40d@e.com").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidContact);
+        assert_eq!(error.detail(), "invalid email address");
+        assert_eq!(error.span(), 0..26);
+        let error = classify("mailto:a%zz@b.com").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidUrl);
+        assert_eq!(error.detail(), "invalid percent-encoding");
+        assert!(classify("mailto:invalid").is_err());
+        assert!(matches!(
+            classify("tel:+1-201-555-0123"),
+            Ok(ContactUri::Phone(_))
+        ));
+        assert!(matches!(
+            classify("tel:7042;phone-context=example.com"),
+            Ok(ContactUri::Phone(_))
+        ));
+        assert!(classify("tel:7042").is_err());
+        assert!(classify("tel:+").is_err());
+        assert!(matches!(
+            classify("https://example.com"),
+            Ok(ContactUri::Web(_))
+        ));
+        assert!(matches!(classify("xmpp:a@b.com"), Ok(ContactUri::Other(_))));
+    }
+
+    #[test]
+    fn recover() {
+        assert_eq!(
+            ContactUri::recover("mail@example.com").map(|uri| uri.to_string()),
+            Some("mailto:mail@example.com".into())
+        );
+        assert_eq!(
+            ContactUri::recover("+44 5555 555 555").map(|uri| uri.to_string()),
+            Some("tel:+44-5555-555-555".into())
+        );
+        assert_eq!(
+            ContactUri::recover("a#b@example.com").map(|uri| uri.to_string()),
+            Some("mailto:a%23b@example.com".into())
+        );
+        assert_eq!(
+            ContactUri::recover("a%41@example.com").map(|uri| uri.to_string()),
+            Some("mailto:a%2541@example.com".into())
+        );
+        assert_eq!(ContactUri::recover("5555 555 555"), None);
+        let error = ContactUri::phone("5555 555 555").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidContact);
+        assert_eq!(error.detail(), "telephone numbers must be in global format");
+        assert_eq!(error.span(), 0..12);
+        let error = ContactUri::email("Invalid").unwrap_err();
+        assert_eq!(error.detail(), "invalid email address");
+        assert_eq!(ContactUri::recover("Invalid"), None);
+    }
+}
@@ -0,0 +1,333 @@
+// https://tools.ietf.org/html/rfc5322#section-3.3
+// https://tools.ietf.org/html/rfc5322#section-4.3
+// https://tools.ietf.org/html/rfc3339#section-5.6
+
+use crate::SpecVersion;
+use chrono::prelude::*;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(&'a str),
+    Comma,
+    Colon,
+    Plus,
+    Minus,
+}
+
+/// The reason a date-time could not be parsed
+type Error = &'static str;
+
+/// Replace comments and folding whitespace (CFWS) with a single space
+fn strip_cfws(string: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(string.len());
+    let mut depth = 0usize;
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if depth > 0 => {
+                // quoted-pair inside a comment
+                chars.next().ok_or("unterminated comment")?;
+            }
+            '(' => depth += 1,
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    result.push(' ');
+                }
+            }
+            ')' => return Err("unbalanced comment"),
+            _ if depth > 0 => {}
+            '\r' | '\n' | '\t' => result.push(' '),
+            c => result.push(c),
+        }
+    }
+    if depth > 0 {
+        return Err("unterminated comment");
+    }
+    Ok(result)
+}
+
+fn tokenize(string: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = string;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == ' ' {
+            1
+        } else if c.is_ascii_alphabetic() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..len]));
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(&rest[..len]));
+            len
+        } else {
+            tokens.push(match c {
+                ',' => Token::Comma,
+                ':' => Token::Colon,
+                '+' => Token::Plus,
+                '-' => Token::Minus,
+                _ => return Err("unexpected character"),
+            });
+            1
+        };
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    Some(match &*name.to_ascii_lowercase() {
+        "mon" => Weekday::Mon,
+        "tue" => Weekday::Tue,
+        "wed" => Weekday::Wed,
+        "thu" => Weekday::Thu,
+        "fri" => Weekday::Fri,
+        "sat" => Weekday::Sat,
+        "sun" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_month(name: &str) -> Option<u32> {
+    Some(match &*name.to_ascii_lowercase() {
+        "jan" => 1,
+        "feb" => 2,
+        "mar" => 3,
+        "apr" => 4,
+        "may" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" => 10,
+        "nov" => 11,
+        "dec" => 12,
+        _ => return None,
+    })
+}
+
+/// The offset in seconds east of UTC for an obsolete zone name
+fn parse_obs_zone(name: &str) -> Option<i32> {
+    let hours = match &*name.to_ascii_uppercase() {
+        "UT" | "GMT" => 0,
+        "EDT" => -4,
+        "EST" | "CDT" => -5,
+        "CST" | "MDT" => -6,
+        "MST" | "PDT" => -7,
+        "PST" => -8,
+        // Military zones were defined incorrectly in RFC 822, so RFC 5322
+        // says they SHOULD be considered equivalent to "-0000"
+        zone if zone.len() == 1 && zone != "J" => 0,
+        _ => return None,
+    };
+    Some(hours * 3600)
+}
+
+fn parse_number(token: Option<&Token<'_>>, digits: RangeInclusive<usize>) -> Option<u32> {
+    match token {
+        Some(Token::Number(number)) if digits.contains(&number.len()) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parse a date-time as specified in RFC 5322, including the obsolete syntax
+pub(crate) fn parse_rfc5322_datetime(string: &str) -> Result<DateTime<FixedOffset>, Error> {
+    let stripped = strip_cfws(string)?;
+    let tokens = tokenize(&stripped)?;
+    let mut tokens = tokens.iter().peekable();
+
+    let weekday = match tokens.peek() {
+        Some(Token::Word(name)) => {
+            tokens.next();
+            let weekday = parse_weekday(name).ok_or("unknown day-of-week")?;
+            if tokens.next() != Some(&Token::Comma) {
+                return Err("missing comma after day-of-week");
+            }
+            Some(weekday)
+        }
+        _ => None,
+    };
+
+    let day = parse_number(tokens.next(), 1..=2).ok_or("invalid day")?;
+    let month = match tokens.next() {
+        Some(Token::Word(name)) => parse_month(name),
+        _ => None,
+    }
+    .ok_or("invalid month")?;
+    let year = match tokens.next() {
+        Some(Token::Number(year)) if year.len() >= 2 => {
+            let value: i32 = year.parse().map_err(|_| "invalid year")?;
+            match year.len() {
+                2 if value < 50 => value + 2000,
+                2 | 3 => value + 1900,
+                _ => value,
+            }
+        }
+        _ => return Err("invalid year"),
+    };
+
+    let hour = parse_number(tokens.next(), 2..=2).ok_or("invalid hour")?;
+    if tokens.next() != Some(&Token::Colon) {
+        return Err("missing colon in time-of-day");
+    }
+    let minute = parse_number(tokens.next(), 2..=2).ok_or("invalid minute")?;
+    let second = if tokens.peek() == Some(&&Token::Colon) {
+        tokens.next();
+        parse_number(tokens.next(), 2..=2).ok_or("invalid second")?
+    } else {
+        0
+    };
+
+    let offset = match tokens.next() {
+        Some(sign @ Token::Plus) | Some(sign @ Token::Minus) => {
+            let zone = parse_number(tokens.next(), 4..=4).ok_or("invalid zone")?;
+            let (hours, minutes) = (zone / 100, zone % 100);
+            if minutes >= 60 {
+                return Err("invalid zone");
+            }
+            let seconds = (hours * 3600 + minutes * 60) as i32;
+            if *sign == Token::Minus {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+        Some(Token::Word(name)) => parse_obs_zone(name).ok_or("unknown zone")?,
+        _ => return Err("missing zone"),
+    };
+    if tokens.next().is_some() {
+        return Err("trailing characters");
+    }
+
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or("out of range")?;
+    if weekday.is_some_and(|weekday| weekday != date.weekday()) {
+        return Err("day-of-week does not match date");
+    }
+    // A leap second is represented by chrono as a fractional 59th second
+    let time = if second == 60 {
+        NaiveTime::from_hms_milli_opt(hour, minute, 59, 1000)
+    } else {
+        NaiveTime::from_hms_opt(hour, minute, second)
+    }
+    .ok_or("out of range")?;
+    FixedOffset::east_opt(offset)
+        .and_then(|offset| offset.from_local_datetime(&date.and_time(time)).single())
+        .ok_or("out of range")
+}
+
+/// Parse a date-time as specified in RFC 3339
+pub(crate) fn parse_rfc3339_datetime(string: &str) -> Result<DateTime<FixedOffset>, Error> {
+    DateTime::parse_from_rfc3339(string).map_err(|_| "not in RFC 3339 format")
+}
+
+/// Parse the value of an `Expires` field
+///
+/// RFC 9116 requires the ISO 8601 format from RFC 3339, while draft 09 used
+/// the RFC 5322 format; the latter also accepts ISO 8601 for compatibility.
+pub(crate) fn parse_datetime(
+    string: &str,
+    spec: SpecVersion,
+) -> Result<DateTime<FixedOffset>, Error> {
+    let string = string.trim();
+    match spec {
+        SpecVersion::Rfc9116 => parse_rfc3339_datetime(string),
+        SpecVersion::Draft09 => parse_rfc3339_datetime(string)
+            .or_else(|_| {
+                DateTime::parse_from_str(string, "%Y-%m-%dT%H:%M:%S%.f%z")
+                    .map_err(|_| "not in ISO 8601 format")
+            })
+            .or_else(|_| parse_rfc5322_datetime(string)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(string: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(string).unwrap()
+    }
+
+    #[test]
+    fn real_world() {
+        let table = [
+            (
+                "Thu, 31 Dec 2020 18:37:07 -0800",
+                "2020-12-31T18:37:07-08:00",
+            ),
+            ("Mon, 31 Jan 2022 23:59:59 +0000", "2022-01-31T23:59:59Z"),
+            ("Sat, 1 Jan 2022 00:00 +0100", "2022-01-01T00:00:00+01:00"),
+            ("31 Dec 2024 23:59:59 GMT", "2024-12-31T23:59:59Z"),
+            ("Tue, 31 Dec 2024 23:59:59 UT", "2024-12-31T23:59:59Z"),
+            ("31 Dec 99 23:59 EST", "1999-12-31T23:59:00-05:00"),
+            ("1 Jan 21 12:00:00 PDT", "2021-01-01T12:00:00-07:00"),
+            ("1 Jan 121 12:00:00 Z", "2021-01-01T12:00:00Z"),
+            ("1 jan 2021 12:00:00 a", "2021-01-01T12:00:00Z"),
+            (
+                "Fri, 21 Nov 1997 09(comment):   55  :  06 -0600",
+                "1997-11-21T09:55:06-06:00",
+            ),
+            (
+                "Fri, 21 Nov 1997\r\n 09:55:06 -0600 (CST (nested \\) comment))",
+                "1997-11-21T09:55:06-06:00",
+            ),
+            ("2021-12-31T18:37:07z", "2021-12-31T18:37:07Z"),
+            ("2024-05-01T00:00:00.000Z", "2024-05-01T00:00:00Z"),
+            ("2023-06-30T22:00:00+02:00", "2023-06-30T22:00:00+02:00"),
+            ("2023-06-30T22:00:00+0200", "2023-06-30T22:00:00+02:00"),
+        ];
+        for (input, expected) in table.iter() {
+            assert_eq!(
+                parse_datetime(input, SpecVersion::Draft09).ok(),
+                Some(datetime(expected)),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn leap_second() {
+        let parsed = parse_rfc5322_datetime("31 Dec 2016 23:59:60 +0000").unwrap();
+        assert_eq!(parsed.second(), 59);
+        assert_eq!(parsed.nanosecond(), 1_000_000_000);
+    }
+
+    #[test]
+    fn invalid() {
+        let table = [
+            "",
+            "Invalid",
+            "Fri, 31 Dec 2020 18:37:07 -0800",
+            "Thu 31 Dec 2020 18:37:07 -0800",
+            "31 Foo 2020 18:37:07 -0800",
+            "32 Dec 2020 18:37:07 -0800",
+            "31 Dec 2020 18:37:07",
+            "31 Dec 2020 18:37:07 -08",
+            "31 Dec 2020 18:37:07 J",
+            "31 Dec 2020 18:37:07 +0000 extra",
+            "31 Dec 2020 (unterminated 18:37:07 +0000",
+        ];
+        for input in table.iter() {
+            assert!(
+                parse_datetime(input, SpecVersion::Draft09).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn rfc9116_requires_rfc3339() {
+        assert!(parse_datetime("2021-12-31T18:37:07Z", SpecVersion::Rfc9116).is_ok());
+        assert!(parse_datetime("2021-12-31T18:37:07+0000", SpecVersion::Rfc9116).is_err());
+        assert!(parse_datetime("31 Dec 2021 18:37:07 +0000", SpecVersion::Rfc9116).is_err());
+    }
+}
@@ -0,0 +1,506 @@
+// https://www.rfc-editor.org/rfc/rfc9116#section-3
+
+use crate::{
+    classify, validate, AsyncFetcher, Diagnostic, FetchError, FetchResponse, Fetcher, Finding,
+    NotSecurityTxt, Rule, SecurityTxt, SpecVersion, LEGACY_PATH, WELL_KNOWN_PATH,
+};
+use std::error::Error;
+use std::fmt;
+use url::Url;
+
+/// Where on a host a file was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// `WELL_KNOWN_PATH`
+    WellKnown,
+    /// `LEGACY_PATH`
+    Legacy,
+}
+
+/// A redirect that was followed while fetching a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// The status of the redirect response, or `None` if the fetcher
+    /// followed the redirect itself
+    pub status: Option<u16>,
+    pub from: Url,
+    pub to: Url,
+}
+
+impl Redirect {
+    /// Whether the redirect leads to another host, which must be noted
+    pub fn is_cross_host(&self) -> bool {
+        self.from.host() != self.to.host() || self.from.port() != self.to.port()
+    }
+}
+
+/// A file found by `discover`
+#[derive(Debug)]
+pub struct Discovery {
+    pub location: Location,
+    /// The URL the file was finally retrieved from
+    pub url: Url,
+    pub redirects: Vec<Redirect>,
+    pub content_type: Option<String>,
+    pub security_txt: SecurityTxt,
+    /// Problems found while parsing the file leniently
+    pub diagnostics: Vec<Diagnostic>,
+    /// Problems with how and where the file was served
+    pub findings: Vec<Finding>,
+}
+
+/// Signifies that no file could be retrieved
+#[derive(Debug)]
+pub enum DiscoveryError {
+    /// The domain could not be turned into a URL
+    InvalidDomain(String),
+    /// A redirect pointed to a URL that is not `https:`
+    InsecureRedirect(Url),
+    /// A redirect had no usable `Location` header
+    InvalidRedirect(Url),
+    /// More than `DiscoveryOptions::max_redirects` redirects were followed
+    TooManyRedirects(Url),
+    /// The server answered with an unsuccessful status at every location tried
+    NotFound(Vec<(Url, u16)>),
+    /// A location answered successfully, but not with a security.txt file
+    NotSecurityTxt(Url, NotSecurityTxt),
+    /// The request could not be sent, or the response could not be read
+    Transport(Url, FetchError),
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDomain(domain) => write!(f, "invalid domain {:?}", domain),
+            Self::InsecureRedirect(url) => write!(f, "refusing insecure redirect to {}", url),
+            Self::InvalidRedirect(url) => write!(f, "invalid redirect from {}", url),
+            Self::TooManyRedirects(url) => write!(f, "too many redirects at {}", url),
+            Self::NotFound(attempts) => {
+                write!(f, "no security.txt found")?;
+                for (i, (url, status)) in attempts.iter().enumerate() {
+                    let separator = if i == 0 { ": " } else { ", " };
+                    write!(f, "{}{} returned {}", separator, url, status)?;
+                }
+                Ok(())
+            }
+            Self::NotSecurityTxt(url, reason) => {
+                write!(f, "{} is not a security.txt file: {}", url, reason)
+            }
+            Self::Transport(url, error) => write!(f, "could not fetch {}: {}", url, error),
+        }
+    }
+}
+
+impl Error for DiscoveryError {}
+
+/// How to discover a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryOptions {
+    /// The version of the specification, which decides whether
+    /// `LEGACY_PATH` is tried, and how the file is parsed
+    pub spec: SpecVersion,
+    pub max_redirects: usize,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            spec: SpecVersion::default(),
+            max_redirects: 10,
+        }
+    }
+}
+
+/// Whether the media type is `text/plain` with a UTF-8 charset
+fn is_plain_utf8(content_type: &str) -> bool {
+    let mut parts = content_type.split(';').map(str::trim);
+    parts
+        .next()
+        .is_some_and(|media_type| media_type.eq_ignore_ascii_case("text/plain"))
+        && parts.any(|parameter| match parameter.split_once('=') {
+            Some((name, value)) => {
+                name.trim().eq_ignore_ascii_case("charset")
+                    && value.trim().trim_matches('"').eq_ignore_ascii_case("utf-8")
+            }
+            None => false,
+        })
+}
+
+/// What a `Session` needs next
+enum Step {
+    Fetch(Url),
+    Done(Box<Discovery>),
+}
+
+/// The state of a discovery between requests, so that the same rules apply
+/// to blocking and asynchronous fetchers
+struct Session {
+    spec: SpecVersion,
+    max_redirects: usize,
+    locations: std::vec::IntoIter<(Location, Url)>,
+    location: Location,
+    redirects: Vec<Redirect>,
+    attempts: Vec<(Url, u16)>,
+    /// The last successful response that was not a security.txt file
+    rejected: Option<(Url, NotSecurityTxt)>,
+}
+
+impl Session {
+    fn new(domain: &str, options: &DiscoveryOptions) -> Result<Self, DiscoveryError> {
+        let base = Url::parse(&format!("https://{}/", domain))
+            .ok()
+            .filter(|url| url.path() == "/" && url.query().is_none() && url.username().is_empty())
+            .ok_or_else(|| DiscoveryError::InvalidDomain(domain.into()))?;
+        let mut locations = vec![(Location::WellKnown, WELL_KNOWN_PATH)];
+        if options.spec.allows_legacy_path() {
+            locations.push((Location::Legacy, LEGACY_PATH));
+        }
+        let locations: Vec<_> = locations
+            .into_iter()
+            .map(|(location, path)| (location, base.join(path).expect("paths are valid")))
+            .collect();
+        Ok(Self {
+            spec: options.spec,
+            max_redirects: options.max_redirects,
+            locations: locations.into_iter(),
+            location: Location::WellKnown,
+            redirects: Vec::new(),
+            attempts: Vec::new(),
+            rejected: None,
+        })
+    }
+
+    /// Move on to the next location, if there is one
+    fn next_location(&mut self) -> Result<Step, DiscoveryError> {
+        match self.locations.next() {
+            Some((location, url)) => {
+                self.location = location;
+                self.redirects.clear();
+                Ok(Step::Fetch(url))
+            }
+            None => Err(match self.rejected.take() {
+                Some((url, reason)) => DiscoveryError::NotSecurityTxt(url, reason),
+                None => DiscoveryError::NotFound(std::mem::take(&mut self.attempts)),
+            }),
+        }
+    }
+
+    /// Handle the response to a request for `url`
+    fn step(
+        &mut self,
+        url: Url,
+        response: Result<FetchResponse, FetchError>,
+    ) -> Result<Step, DiscoveryError> {
+        let response = response.map_err(|error| DiscoveryError::Transport(url.clone(), error))?;
+        if response.url != url {
+            if response.url.scheme() != "https" {
+                return Err(DiscoveryError::InsecureRedirect(response.url));
+            }
+            self.redirects.push(Redirect {
+                status: None,
+                from: url,
+                to: response.url.clone(),
+            });
+        }
+        let url = &response.url;
+
+        if (300..400).contains(&response.status) {
+            if self.redirects.len() >= self.max_redirects {
+                return Err(DiscoveryError::TooManyRedirects(response.url));
+            }
+            let to = match response
+                .header("Location")
+                .and_then(|location| url.join(location).ok())
+            {
+                Some(to) => to,
+                None => return Err(DiscoveryError::InvalidRedirect(response.url)),
+            };
+            if to.scheme() != "https" {
+                return Err(DiscoveryError::InsecureRedirect(to));
+            }
+            self.redirects.push(Redirect {
+                status: Some(response.status),
+                from: response.url,
+                to: to.clone(),
+            });
+            return Ok(Step::Fetch(to));
+        }
+        if !(200..300).contains(&response.status) {
+            self.attempts.push((response.url, response.status));
+            return self.next_location();
+        }
+
+        let body = String::from_utf8_lossy(&response.body);
+        let content_type = response.header("Content-Type").map(String::from);
+        let (security_txt, diagnostics) = SecurityTxt::parse_with_diagnostics(&body, self.spec);
+        if let Err(reason) = classify::check(&body, content_type.as_deref(), &security_txt) {
+            self.rejected = Some((response.url, reason));
+            return self.next_location();
+        }
+        let mut findings = self.findings(content_type.as_deref());
+        findings.extend(validate::check_canonical(
+            security_txt.fields(),
+            &response.url,
+        ));
+        Ok(Step::Done(Box::new(Discovery {
+            location: self.location,
+            findings,
+            url: response.url,
+            redirects: std::mem::take(&mut self.redirects),
+            content_type,
+            security_txt,
+            diagnostics,
+        })))
+    }
+
+    /// Problems with how the file was served
+    fn findings(&self, content_type: Option<&str>) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for redirect in &self.redirects {
+            if redirect.is_cross_host() {
+                let message = format!(
+                    "redirected from {} to another host at {}",
+                    redirect.from, redirect.to
+                );
+                findings.push(Finding::new(Rule::CrossHostRedirect, None, message));
+            } else {
+                let message = format!("redirected from {} to {}", redirect.from, redirect.to);
+                findings.push(Finding::new(Rule::Redirected, None, message));
+            }
+        }
+        match content_type {
+            Some(content_type) if is_plain_utf8(content_type) => {}
+            Some(content_type) => {
+                let message = format!(
+                    "the file was served as {:?} instead of \"text/plain; charset=utf-8\"",
+                    content_type
+                );
+                findings.push(Finding::new(Rule::ContentTypeInvalid, None, message));
+            }
+            None => {
+                let message = "the file was served without a Content-Type";
+                findings.push(Finding::new(Rule::ContentTypeInvalid, None, message));
+            }
+        }
+        findings
+    }
+}
+
+/// Look for the file of a domain over HTTPS using ureq, with the default
+/// `DiscoveryOptions`
+#[cfg(feature = "ureq")]
+pub fn discover(domain: &str) -> Result<Discovery, DiscoveryError> {
+    discover_with(
+        domain,
+        &DiscoveryOptions::default(),
+        &crate::UreqFetcher::new(),
+    )
+}
+
+/// Look for the file of a domain at `WELL_KNOWN_PATH`, falling back to
+/// `LEGACY_PATH` if the specification allows it
+///
+/// Only HTTPS is used, and redirects to other schemes are refused.
+pub fn discover_with<F>(
+    domain: &str,
+    options: &DiscoveryOptions,
+    fetcher: &F,
+) -> Result<Discovery, DiscoveryError>
+where
+    F: Fetcher + ?Sized,
+{
+    let mut session = Session::new(domain, options)?;
+    let mut step = session.next_location()?;
+    loop {
+        match step {
+            Step::Fetch(url) => {
+                let response = fetcher.fetch(&url);
+                step = session.step(url, response)?;
+            }
+            Step::Done(discovery) => return Ok(*discovery),
+        }
+    }
+}
+
+/// Like `discover_with`, but using an asynchronous fetcher
+pub async fn discover_async<F>(
+    domain: &str,
+    options: &DiscoveryOptions,
+    fetcher: &F,
+) -> Result<Discovery, DiscoveryError>
+where
+    F: AsyncFetcher + ?Sized,
+{
+    let mut session = Session::new(domain, options)?;
+    let mut step = session.next_location()?;
+    loop {
+        match step {
+            Step::Fetch(url) => {
+                let response = fetcher.fetch(&url).await;
+                step = session.step(url, response)?;
+            }
+            Step::Done(discovery) => return Ok(*discovery),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockFetcher;
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    #[test]
+    fn content_type() {
+        assert!(is_plain_utf8("text/plain; charset=utf-8"));
+        assert!(is_plain_utf8("Text/Plain;Charset=\"UTF-8\""));
+        assert!(is_plain_utf8("text/plain; format=flowed; charset=utf-8"));
+        assert!(!is_plain_utf8("text/plain"));
+        assert!(!is_plain_utf8("text/plain; charset=iso-8859-1"));
+        assert!(!is_plain_utf8("text/html; charset=utf-8"));
+    }
+
+    #[test]
+    fn invalid_domain() {
+        let fetcher = MockFetcher::new();
+        for domain in ["", "example.com/path", "user@example.com", "example.com?q"].iter() {
+            assert!(matches!(
+                discover_with(domain, &DiscoveryOptions::default(), &fetcher),
+                Err(DiscoveryError::InvalidDomain(_))
+            ));
+        }
+        assert!(fetcher.requests().is_empty());
+    }
+
+    #[test]
+    fn fetcher_following_redirects() {
+        let mut response = FetchResponse {
+            status: 200,
+            headers: vec![("content-type".into(), "text/plain; charset=utf-8".into())],
+            url: Url::parse("https://www.example.com/.well-known/security.txt").unwrap(),
+            body: b"Contact: mailto:a@b.com\n".to_vec(),
+        };
+        let fetcher = FollowingFetcher(response.clone());
+        let discovery =
+            discover_with("example.com", &DiscoveryOptions::default(), &fetcher).unwrap();
+        assert_eq!(discovery.redirects[0].status, None);
+        assert!(discovery.redirects[0].is_cross_host());
+        assert_eq!(discovery.url, response.url);
+        assert_eq!(
+            discovery.content_type.as_deref(),
+            Some("text/plain; charset=utf-8")
+        );
+
+        response.url = Url::parse("http://example.com/.well-known/security.txt").unwrap();
+        assert!(matches!(
+            discover_with(
+                "example.com",
+                &DiscoveryOptions::default(),
+                &FollowingFetcher(response)
+            ),
+            Err(DiscoveryError::InsecureRedirect(_))
+        ));
+    }
+
+    #[test]
+    fn soft_404() {
+        let options = DiscoveryOptions {
+            spec: SpecVersion::Draft09,
+            ..DiscoveryOptions::default()
+        };
+        let html = include_str!("../tests/files/lobste.rs.txt");
+        let fetcher = MockFetcher::new()
+            .with_body(
+                "https://example.com/.well-known/security.txt",
+                "text/html",
+                html,
+            )
+            .with_body(
+                "https://example.com/security.txt",
+                "text/plain; charset=utf-8",
+                "Contact: mailto:a@b.com\n",
+            );
+        let discovery = discover_with("example.com", &options, &fetcher).unwrap();
+        assert_eq!(discovery.location, Location::Legacy);
+
+        let fetcher = MockFetcher::new().with_body(
+            "https://example.com/.well-known/security.txt",
+            "text/html",
+            html,
+        );
+        match discover_with("example.com", &options, &fetcher) {
+            Err(DiscoveryError::NotSecurityTxt(url, reason)) => {
+                assert_eq!(url.path(), WELL_KNOWN_PATH);
+                assert_eq!(reason, NotSecurityTxt::HtmlDetected);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        let body = "Contact: mailto:a@b.com\n\
+                    Canonical: https://example.com/.well-known/security.txt\n";
+        let fetcher = MockFetcher::new()
+            .with_body(
+                "https://example.com/.well-known/security.txt",
+                "text/plain; charset=utf-8",
+                body,
+            )
+            .with_body(
+                "https://copy.example/.well-known/security.txt",
+                "text/plain; charset=utf-8",
+                body,
+            );
+        let options = DiscoveryOptions::default();
+        let discovery = discover_with("EXAMPLE.com:443", &options, &fetcher).unwrap();
+        assert!(discovery.findings.is_empty());
+        let discovery = discover_with("copy.example", &options, &fetcher).unwrap();
+        assert_eq!(discovery.findings[0].rule, Rule::CanonicalMismatch);
+        assert_eq!(
+            discovery.findings[0].message,
+            "the file was retrieved from https://copy.example/.well-known/security.txt, \
+             which is not a Canonical URI: https://example.com/.well-known/security.txt"
+        );
+    }
+
+    #[test]
+    fn asynchronous() {
+        let fetcher = MockFetcher::new()
+            .with_redirect(
+                "https://example.com/.well-known/security.txt",
+                "/security.txt",
+            )
+            .with_body(
+                "https://example.com/security.txt",
+                "text/plain; charset=utf-8",
+                "Contact: mailto:a@b.com\n",
+            );
+        let options = DiscoveryOptions::default();
+        let discovery = block_on(discover_async("example.com", &options, &fetcher)).unwrap();
+        assert_eq!(discovery.location, Location::WellKnown);
+        assert_eq!(discovery.redirects[0].status, Some(301));
+        assert_eq!(discovery.url.path(), "/security.txt");
+        assert_eq!(fetcher.requests().len(), 2);
+    }
+
+    /// A fetcher that followed a redirect to the response's URL by itself
+    struct FollowingFetcher(FetchResponse);
+
+    impl Fetcher for FollowingFetcher {
+        fn fetch(&self, _: &Url) -> Result<FetchResponse, FetchError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Poll a future that never waits to completion
+    fn block_on<T>(future: impl Future<Output = T>) -> T {
+        let mut future = Box::pin(future);
+        let mut context = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the future is waiting"),
+        }
+    }
+}
@@ -0,0 +1,623 @@
+use crate::cleartext::{self, BEGIN_SIGNATURE, BEGIN_SIGNED_MESSAGE, END_SIGNATURE};
+use crate::{
+    check_extension, sign, ContactUri, Diagnostic, Field, FieldKind, ParseError, SecurityTxt,
+    SignError, Signer, SpecVersion,
+};
+use chrono::{DateTime, FixedOffset};
+use language_tags::LanguageTag;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/// Signifies that an extension field could not be set, such as one named like
+/// a field of the specification or with a line break in its value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionError {
+    pub name: String,
+    pub detail: String,
+}
+
+impl fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid extension field {:?}: {}",
+            self.name, self.detail
+        )
+    }
+}
+
+impl Error for ExtensionError {}
+
+/// What a line of a file is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineKind {
+    /// A field, or a line meant to be one that could not be parsed
+    Field,
+    /// A comment, starting with `#`
+    Comment,
+    /// An empty line, or one with only whitespace
+    Blank,
+    /// Part of the OpenPGP framing of a signed file: the header line, the
+    /// armor headers and the empty line after them, and the signature
+    SignatureArmor,
+}
+
+/// A single line of a file, exactly as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line<'a> {
+    kind: LineKind,
+    /// `"- "` if the line is dash-escaped in the signed text, or empty
+    escape: &'a str,
+    text: Cow<'a, str>,
+    /// `"\n"` or `"\r\n"`, or empty for a last line without a line ending
+    ending: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn kind(&self) -> LineKind {
+        self.kind
+    }
+
+    /// The contents of the line, without any dash-escape or line ending
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the line is dash-escaped in the signed text
+    pub fn is_escaped(&self) -> bool {
+        !self.escape.is_empty()
+    }
+
+    /// The line ending, which is empty for a last line without one
+    pub fn ending(&self) -> &'a str {
+        self.ending
+    }
+
+    /// The name of a field as written, if the line is a field with a colon
+    pub fn name(&self) -> Option<&str> {
+        self.split().map(|(name, _)| name)
+    }
+
+    /// The value of a field without surrounding whitespace, if the line is a
+    /// field with a colon
+    pub fn value(&self) -> Option<&str> {
+        self.split().map(|(_, value)| value.trim())
+    }
+
+    fn split(&self) -> Option<(&str, &str)> {
+        match self.kind {
+            LineKind::Field => self.text.split_once(':'),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.escape, self.text, self.ending)
+    }
+}
+
+/// Where a line is in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    ArmorHeaders,
+    Text,
+    Signature,
+    AfterSignature,
+}
+
+/// A file as the lines it was written with, borrowing from the input
+///
+/// Unlike `SecurityTxt`, this keeps comments, spacing, casing and line
+/// endings, and is printed exactly as it was parsed. Editing it rewrites only
+/// the lines of the fields edited.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Document<'a> {
+    lines: Vec<Line<'a>>,
+    /// Whether a signed file has been edited since it was parsed
+    signature_invalidated: bool,
+}
+
+impl<'a> Document<'a> {
+    /// Split a file into lines
+    ///
+    /// This never fails, since lines are kept as they are even if they are not
+    /// valid; converting to `SecurityTxt` reports any problems.
+    pub fn parse(string: &'a str) -> Self {
+        let signed = cleartext::is_signed(string);
+        let mut section = if signed {
+            Section::ArmorHeaders
+        } else {
+            Section::Text
+        };
+        let mut lines = Vec::new();
+        for (index, raw) in string.split_inclusive('\n').enumerate() {
+            let text = raw.strip_suffix('\n').unwrap_or(raw);
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            let ending = &raw[text.len()..];
+            let trimmed = text.trim_end();
+            let mut escape = "";
+            let kind = match section {
+                Section::ArmorHeaders => {
+                    if index > 0 && trimmed.is_empty() {
+                        section = Section::Text;
+                    }
+                    LineKind::SignatureArmor
+                }
+                Section::Text if signed && trimmed == BEGIN_SIGNATURE => {
+                    section = Section::Signature;
+                    LineKind::SignatureArmor
+                }
+                Section::Text => {
+                    let content = match text.strip_prefix("- ") {
+                        Some(unescaped) if signed => {
+                            escape = &text[..2];
+                            unescaped
+                        }
+                        _ => text,
+                    };
+                    if content.trim().is_empty() {
+                        LineKind::Blank
+                    } else if content.starts_with('#') {
+                        LineKind::Comment
+                    } else {
+                        LineKind::Field
+                    }
+                }
+                Section::Signature => {
+                    if trimmed == END_SIGNATURE {
+                        section = Section::AfterSignature;
+                    }
+                    LineKind::SignatureArmor
+                }
+                Section::AfterSignature if trimmed.is_empty() => LineKind::Blank,
+                Section::AfterSignature => LineKind::SignatureArmor,
+            };
+            lines.push(Line {
+                kind,
+                escape,
+                text: Cow::Borrowed(&text[escape.len()..]),
+                ending,
+            });
+        }
+        Self {
+            lines,
+            signature_invalidated: false,
+        }
+    }
+
+    pub fn lines(&self) -> &[Line<'a>] {
+        &self.lines
+    }
+
+    /// Whether the file is an OpenPGP cleartext signed message
+    pub fn is_signed(&self) -> bool {
+        self.lines
+            .first()
+            .is_some_and(|line| line.kind == LineKind::SignatureArmor)
+    }
+
+    /// Whether the file is signed and has been edited since it was parsed, so
+    /// that its signature no longer matches and it must be signed again
+    pub fn is_signature_invalidated(&self) -> bool {
+        self.signature_invalidated
+    }
+
+    /// Set the `Expires` field, replacing any existing ones
+    pub fn set_expires(&mut self, expires: DateTime<FixedOffset>) {
+        self.set_field(FieldKind::Expires, Field::Expires(expires));
+    }
+
+    /// Set the `Preferred-Languages` field, replacing any existing ones, or
+    /// remove it if `languages` is empty
+    pub fn set_preferred_languages(&mut self, languages: Vec<LanguageTag>) {
+        if languages.is_empty() {
+            self.remove_field(FieldKind::PreferredLanguages);
+        } else {
+            let field = Field::PreferredLanguages(languages);
+            self.set_field(FieldKind::PreferredLanguages, field);
+        }
+    }
+
+    /// Add a `Contact` field after the existing ones
+    pub fn add_contact(&mut self, contact: ContactUri) {
+        let last = self.field_lines(FieldKind::Contact).last();
+        self.insert(last, Field::Contact(contact).to_string());
+    }
+
+    /// Remove every field of the given kind, including those written with a
+    /// legacy name, and return how many were removed
+    pub fn remove_field(&mut self, kind: FieldKind) -> usize {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !line.name().is_some_and(|name| kind.matches(name)));
+        let removed = before - self.lines.len();
+        if removed > 0 {
+            self.invalidate_signature();
+        }
+        removed
+    }
+
+    /// Set the value of the extension field with the given name, compared
+    /// case-insensitively, replacing any others with the same name
+    ///
+    /// The name is kept as it was written if the field already exists. Fields
+    /// of the specification are refused; use their own methods instead.
+    pub fn replace_extension(&mut self, name: &str, value: &str) -> Result<(), ExtensionError> {
+        check_extension(name, value)?;
+        let matches = |line: &Line| line.name().is_some_and(|n| n.eq_ignore_ascii_case(name));
+        let written = self.lines.iter().find(|line| matches(line));
+        let name = written.and_then(Line::name).unwrap_or(name);
+        let text = Field::Extension(name.into(), value.into()).to_string();
+        self.set_line(matches, text);
+        Ok(())
+    }
+
+    /// Remove the OpenPGP framing of a signed file, leaving the signed text as
+    /// an unsigned file
+    pub fn strip_signature(&mut self) {
+        if !self.is_signed() {
+            return;
+        }
+        let end = self.text_end();
+        let start = self
+            .lines
+            .iter()
+            .position(|line| line.kind != LineKind::SignatureArmor)
+            .map_or(end, |start| start.min(end));
+        self.lines.truncate(end);
+        self.lines.drain(..start);
+        for line in &mut self.lines {
+            line.escape = "";
+        }
+        self.signature_invalidated = false;
+    }
+
+    /// Sign the file with `signer` as an OpenPGP cleartext signed message,
+    /// replacing any existing signature
+    pub fn sign<S: Signer + ?Sized>(&mut self, signer: &S) -> Result<(), SignError> {
+        let mut signed = self.clone();
+        signed.strip_signature();
+        let eol = signed.line_ending();
+
+        // The line ending of the last line is not part of the signed text
+        let text = signed.to_string();
+        let text = text.strip_suffix('\n').unwrap_or(&text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        let (hash, armored) = sign::sign_text(signer, text)?;
+
+        if let Some(last) = signed.lines.last_mut() {
+            if last.ending.is_empty() {
+                last.ending = eol;
+            }
+        }
+        for line in &mut signed.lines {
+            if line.text.starts_with('-') {
+                line.escape = "- ";
+            }
+        }
+        let armor_line = |text: String| Line {
+            kind: LineKind::SignatureArmor,
+            escape: "",
+            text: Cow::Owned(text),
+            ending: eol,
+        };
+        let header = [
+            BEGIN_SIGNED_MESSAGE.to_string(),
+            format!("Hash: {}", hash),
+            String::new(),
+        ];
+        signed.lines.splice(0..0, header.map(armor_line));
+        signed
+            .lines
+            .extend(armored.split('\n').map(|line| armor_line(line.into())));
+        *self = signed;
+        Ok(())
+    }
+
+    /// The indices of the lines of fields of the given kind
+    fn field_lines(&self, kind: FieldKind) -> impl Iterator<Item = usize> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(move |(_, line)| line.name().is_some_and(|name| kind.matches(name)))
+            .map(|(index, _)| index)
+    }
+
+    fn set_field(&mut self, kind: FieldKind, field: Field) {
+        self.set_line(
+            |line| line.name().is_some_and(|name| kind.matches(name)),
+            field.to_string(),
+        );
+    }
+
+    /// Replace the first line that matches with `text` and remove the others,
+    /// or add `text` as a new field if none match
+    fn set_line(&mut self, matches: impl Fn(&Line) -> bool, text: String) {
+        let first = match self.lines.iter().position(&matches) {
+            Some(first) => first,
+            None => return self.insert(None, text),
+        };
+        let escape = self.escape(&text);
+        let line = &mut self.lines[first];
+        line.escape = escape;
+        line.text = Cow::Owned(text);
+        let mut index = 0;
+        self.lines.retain(|line| {
+            let keep = index == first || !matches(line);
+            index += 1;
+            keep
+        });
+        self.invalidate_signature();
+    }
+
+    /// Insert a field line after the line at `after`, or after the last
+    /// field or comment of the signed text if `None`
+    fn insert(&mut self, after: Option<usize>, text: String) {
+        let index = match after {
+            Some(after) => after + 1,
+            None => {
+                let end = self.text_end();
+                self.lines[..end]
+                    .iter()
+                    .rposition(|line| matches!(line.kind, LineKind::Field | LineKind::Comment))
+                    .map_or(end, |last| last + 1)
+            }
+        };
+        let ending = self.line_ending();
+        let ending = match index
+            .checked_sub(1)
+            .map(|previous| &mut self.lines[previous])
+        {
+            // The new line becomes the last one, so it takes the missing line ending
+            Some(previous) if previous.ending.is_empty() => {
+                previous.ending = ending;
+                ""
+            }
+            _ => ending,
+        };
+        let line = Line {
+            kind: LineKind::Field,
+            escape: self.escape(&text),
+            text: Cow::Owned(text),
+            ending,
+        };
+        self.lines.insert(index, line);
+        self.invalidate_signature();
+    }
+
+    /// The index of the line after the signed text, or the number of lines
+    /// for unsigned files
+    fn text_end(&self) -> usize {
+        if !self.is_signed() {
+            return self.lines.len();
+        }
+        self.lines
+            .iter()
+            .position(|line| {
+                line.kind == LineKind::SignatureArmor && line.text.trim_end() == BEGIN_SIGNATURE
+            })
+            .unwrap_or(self.lines.len())
+    }
+
+    /// The line ending used by the file, which is LF if it has none
+    fn line_ending(&self) -> &'a str {
+        self.lines
+            .iter()
+            .map(|line| line.ending)
+            .find(|ending| !ending.is_empty())
+            .unwrap_or("\n")
+    }
+
+    /// The dash-escape a new line needs
+    fn escape(&self, text: &str) -> &'static str {
+        if self.is_signed() && text.starts_with('-') {
+            "- "
+        } else {
+            ""
+        }
+    }
+
+    fn invalidate_signature(&mut self) {
+        if self.is_signed() {
+            self.signature_invalidated = true;
+        }
+    }
+
+    /// Parse the fields according to the given version of the specification
+    pub fn to_security_txt(&self, spec: SpecVersion) -> Result<SecurityTxt, ParseError> {
+        SecurityTxt::parse_with_spec(&self.to_string(), spec)
+    }
+
+    /// Parse the fields without aborting on errors, like
+    /// `SecurityTxt::parse_with_diagnostics`
+    pub fn to_security_txt_with_diagnostics(
+        &self,
+        spec: SpecVersion,
+    ) -> (SecurityTxt, Vec<Diagnostic>) {
+        SecurityTxt::parse_with_diagnostics(&self.to_string(), spec)
+    }
+}
+
+impl fmt::Display for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn kinds(document: &Document) -> Vec<LineKind> {
+        document.lines().iter().map(Line::kind).collect()
+    }
+
+    #[test]
+    fn unsigned() {
+        let input = "# Comment\r\n\r\nContact:  mailto:a@b.com \r\nexpires: 2030-01-01T00:00:00Z";
+        let document = Document::parse(input);
+        assert_eq!(document.to_string(), input);
+        assert!(!document.is_signed());
+        assert_eq!(
+            kinds(&document),
+            [
+                LineKind::Comment,
+                LineKind::Blank,
+                LineKind::Field,
+                LineKind::Field
+            ]
+        );
+        let contact = &document.lines()[2];
+        assert_eq!(contact.name(), Some("Contact"));
+        assert_eq!(contact.value(), Some("mailto:a@b.com"));
+        assert_eq!(contact.ending(), "\r\n");
+        assert_eq!(document.lines()[3].name(), Some("expires"));
+        assert_eq!(document.lines()[3].ending(), "");
+        assert_eq!(document.lines()[0].name(), None);
+        assert_eq!(
+            document.to_security_txt(SpecVersion::Rfc9116),
+            SecurityTxt::parse_with_spec(input, SpecVersion::Rfc9116)
+        );
+    }
+
+    #[test]
+    fn signed() {
+        let input = format!(
+            "{}\nHash: SHA256\n\n- Contact: mailto:a@b.com\n- -Dashed\n\n{}\n\n{}\n\n",
+            cleartext::BEGIN_SIGNED_MESSAGE,
+            BEGIN_SIGNATURE,
+            END_SIGNATURE
+        );
+        let document = Document::parse(&input);
+        assert_eq!(document.to_string(), input);
+        assert!(document.is_signed());
+        assert_eq!(
+            kinds(&document),
+            [
+                LineKind::SignatureArmor,
+                LineKind::SignatureArmor,
+                LineKind::SignatureArmor,
+                LineKind::Field,
+                LineKind::Field,
+                LineKind::Blank,
+                LineKind::SignatureArmor,
+                LineKind::SignatureArmor,
+                LineKind::SignatureArmor,
+                LineKind::Blank,
+            ]
+        );
+        let contact = &document.lines()[3];
+        assert!(contact.is_escaped());
+        assert_eq!(contact.text(), "Contact: mailto:a@b.com");
+        assert_eq!(contact.name(), Some("Contact"));
+        assert_eq!(document.lines()[4].text(), "-Dashed");
+    }
+
+    #[test]
+    fn editing() {
+        let input = "# Contact details\r\n\
+                     Contact: mailto:a@b.com\r\n\
+                     expires: 2021-01-01T00:00:00Z\r\n\
+                     Preferred-Language: en\r\n\
+                     \r\n\
+                     # Extensions\r\n\
+                     csaf: https://b.com/old.json\r\n\
+                     CSAF: https://b.com/older.json\r\n\
+                     Policy: https://b.com/policy";
+        let mut document = Document::parse(input);
+        document.set_expires(DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z").unwrap());
+        document.add_contact(ContactUri::phone("+1 201 555 0123").unwrap());
+        document.set_preferred_languages(vec![
+            LanguageTag::from_str("en").unwrap(),
+            LanguageTag::from_str("da").unwrap(),
+        ]);
+        document
+            .replace_extension("CSAF", "https://b.com/new.json")
+            .unwrap();
+        assert_eq!(document.remove_field(FieldKind::Hiring), 0);
+        assert!(!document.is_signature_invalidated());
+        assert_eq!(
+            document.to_string(),
+            "# Contact details\r\n\
+             Contact: mailto:a@b.com\r\n\
+             Contact: tel:+1-201-555-0123\r\n\
+             Expires: 2030-01-01T00:00:00Z\r\n\
+             Preferred-Languages: en, da\r\n\
+             \r\n\
+             # Extensions\r\n\
+             csaf: https://b.com/new.json\r\n\
+             Policy: https://b.com/policy"
+        );
+
+        assert_eq!(document.remove_field(FieldKind::Contact), 2);
+        document.add_contact(ContactUri::email("c@b.com").unwrap());
+        document.replace_extension("Hash", "none").unwrap();
+        document.set_preferred_languages(Vec::new());
+        assert_eq!(
+            document.to_string(),
+            "# Contact details\r\n\
+             Expires: 2030-01-01T00:00:00Z\r\n\
+             \r\n\
+             # Extensions\r\n\
+             csaf: https://b.com/new.json\r\n\
+             Policy: https://b.com/policy\r\n\
+             Contact: mailto:c@b.com\r\n\
+             Hash: none"
+        );
+        let unchanged = document.to_string();
+        for (name, value) in [
+            ("Expires", "garbage"),
+            ("contact", "nonsense"),
+            ("Preferred-Language", "en"),
+            ("CSAF", "value\nContact: https://evil.example"),
+            ("My Field", "value"),
+        ]
+        .iter()
+        {
+            assert!(document.replace_extension(name, value).is_err(), "{}", name);
+        }
+        assert_eq!(document.to_string(), unchanged);
+
+        let mut empty = Document::default();
+        empty.add_contact(ContactUri::email("a@b.com").unwrap());
+        assert_eq!(empty.to_string(), "Contact: mailto:a@b.com\n");
+    }
+
+    #[test]
+    fn editing_signed() {
+        let input = format!(
+            "{}\nHash: SHA256\n\nContact: mailto:a@b.com\n\n{}\n\n{}\n",
+            cleartext::BEGIN_SIGNED_MESSAGE,
+            BEGIN_SIGNATURE,
+            END_SIGNATURE
+        );
+        let mut document = Document::parse(&input);
+        document.replace_extension("-Dashed", "value").unwrap();
+        assert!(document.is_signature_invalidated());
+        assert_eq!(
+            document.to_string(),
+            format!(
+                "{}\nHash: SHA256\n\nContact: mailto:a@b.com\n- -Dashed: value\n\n{}\n\n{}\n",
+                cleartext::BEGIN_SIGNED_MESSAGE,
+                BEGIN_SIGNATURE,
+                END_SIGNATURE
+            )
+        );
+        let (security_txt, _) = document.to_security_txt_with_diagnostics(SpecVersion::Rfc9116);
+        assert_eq!(security_txt.extension("-dashed"), Some("value"));
+    }
+
+    #[test]
+    fn empty() {
+        let document = Document::parse("");
+        assert!(document.lines().is_empty());
+        assert_eq!(document.to_string(), "");
+    }
+}
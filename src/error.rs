@@ -0,0 +1,192 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The kind of problem encountered while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The line is neither a comment nor a `name: value` field
+    MissingColon,
+    /// The field name is a legacy or misspelled form of a known field
+    DeprecatedName,
+    /// The value of a field that requires a URI could not be parsed
+    InvalidUrl,
+    /// The email address or telephone number of a `Contact` URI is malformed
+    InvalidContact,
+    /// A `Contact` field holds a bare email address or telephone number
+    /// instead of a URI
+    BareContact,
+    /// The value of the `Expires` field could not be parsed
+    InvalidDate,
+    /// One of the `Preferred-Languages` could not be parsed
+    InvalidLanguageTag,
+    /// A field that must only appear once appeared again
+    DuplicateField,
+    /// The OpenPGP signature framing is malformed
+    InvalidSignature,
+}
+
+impl ErrorKind {
+    /// A stable identifier for the kind
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::MissingColon => "missing-colon",
+            Self::DeprecatedName => "deprecated-name",
+            Self::InvalidUrl => "invalid-url",
+            Self::InvalidContact => "invalid-contact",
+            Self::BareContact => "bare-contact",
+            Self::InvalidDate => "invalid-date",
+            Self::InvalidLanguageTag => "invalid-language-tag",
+            Self::DuplicateField => "duplicate-field",
+            Self::InvalidSignature => "invalid-signature",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingColon => "missing colon after field name",
+            Self::DeprecatedName => "deprecated field name",
+            Self::InvalidUrl => "invalid URL",
+            Self::InvalidContact => "invalid contact URI",
+            Self::BareContact => "contact is not a URI",
+            Self::InvalidDate => "invalid date-time",
+            Self::InvalidLanguageTag => "invalid language tag",
+            Self::DuplicateField => "duplicate field",
+            Self::InvalidSignature => "invalid signature",
+        })
+    }
+}
+
+/// Signifies an error in the specification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ErrorKind,
+    line: usize,
+    span: Range<usize>,
+    field: Option<String>,
+    detail: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ErrorKind, span: Range<usize>) -> Self {
+        Self {
+            kind,
+            line: 1,
+            span,
+            field: None,
+            detail: String::new(),
+        }
+    }
+
+    pub(crate) fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub(crate) fn with_detail(mut self, detail: impl fmt::Display) -> Self {
+        self.detail = detail.to_string();
+        self
+    }
+
+    /// Move the error to the given line, which starts at `offset` in the input
+    pub(crate) fn at_line(mut self, line: usize, offset: usize) -> Self {
+        self.line = line;
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+
+    /// What went wrong
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The 1-based line number the error occurred on
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The byte range in the input that caused the error
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The name of the field the error occurred in, as written in the input
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// More information about what went wrong, which may be empty
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)?;
+        if let Some(field) = &self.field {
+            write!(f, " in {} field", field)?;
+        }
+        if !self.detail.is_empty() {
+            write!(f, ": {}", self.detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The input could be improved, but follows the specification
+    Info,
+    /// The input is usable, but does not follow the specification
+    Warning,
+    /// The input violates the specification
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// A problem found while parsing in lenient mode
+///
+/// Lines with a problem of `Severity::Error` could not be parsed, and were
+/// skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: ParseError,
+}
+
+impl Diagnostic {
+    pub(crate) fn error(error: ParseError) -> Self {
+        Self {
+            severity: Severity::Error,
+            error,
+        }
+    }
+
+    pub(crate) fn warning(error: ParseError) -> Self {
+        Self {
+            severity: Severity::Warning,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::sync::Mutex;
+#[cfg(any(feature = "ureq", feature = "reqwest"))]
+use std::time::Duration;
+use url::Url;
+
+/// How long the default clients wait to connect
+#[cfg(any(feature = "ureq", feature = "reqwest"))]
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the default clients wait for a whole request
+#[cfg(any(feature = "ureq", feature = "reqwest"))]
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default limit on the size of a response body, in bytes
+#[cfg(any(feature = "ureq", feature = "reqwest"))]
+pub(crate) const BODY_LIMIT: u64 = 1024 * 1024;
+
+/// A response to a single HTTP GET request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchResponse {
+    pub status: u16,
+    /// The header names and values, in the order they were received
+    pub headers: Vec<(String, String)>,
+    /// The URL the response came from, which differs from the requested URL
+    /// only if the fetcher followed redirects itself
+    pub url: Url,
+    pub body: Vec<u8>,
+}
+
+impl FetchResponse {
+    /// The value of the first header with the given name, compared
+    /// case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+}
+
+/// Signifies that a request could not be sent, or its response could not be
+/// read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchError(String);
+
+impl FetchError {
+    pub fn new(detail: impl fmt::Display) -> Self {
+        Self(detail.to_string())
+    }
+
+    /// The response body was larger than `limit` bytes
+    #[cfg(any(feature = "ureq", feature = "reqwest"))]
+    pub(crate) fn too_large(limit: u64) -> Self {
+        Self::new(format!("the response is larger than {} bytes", limit))
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for FetchError {}
+
+/// Sends HTTP GET requests for discovery, using a blocking HTTP client
+///
+/// Fetchers should not follow redirects, so that discovery can check and
+/// report each of them.
+pub trait Fetcher {
+    fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError>;
+}
+
+/// The future returned by `AsyncFetcher::fetch`
+pub type FetchFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<FetchResponse, FetchError>> + Send + 'a>>;
+
+/// Sends HTTP GET requests for discovery, using an asynchronous HTTP client
+///
+/// Fetchers should not follow redirects, so that discovery can check and
+/// report each of them.
+pub trait AsyncFetcher {
+    fn fetch<'a>(&'a self, url: &'a Url) -> FetchFuture<'a>;
+}
+
+/// Answers requests from a fixed set of responses, for tests
+///
+/// Requests for unknown URLs are answered with `404 Not Found`.
+#[derive(Debug, Default)]
+pub struct MockFetcher {
+    responses: HashMap<Url, FetchResponse>,
+    requests: Mutex<Vec<Url>>,
+}
+
+impl MockFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests for `response.url` with the response
+    pub fn with(mut self, response: FetchResponse) -> Self {
+        self.responses.insert(response.url.clone(), response);
+        self
+    }
+
+    /// Answer requests for `url` with a `200 OK` response
+    pub fn with_body(self, url: &str, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        self.with(FetchResponse {
+            status: 200,
+            headers: vec![("Content-Type".into(), content_type.into())],
+            url: Url::parse(url).expect("invalid mock URL"),
+            body: body.into(),
+        })
+    }
+
+    /// Answer requests for `from` with a `301 Moved Permanently` redirect
+    pub fn with_redirect(self, from: &str, to: &str) -> Self {
+        self.with(FetchResponse {
+            status: 301,
+            headers: vec![("Location".into(), to.into())],
+            url: Url::parse(from).expect("invalid mock URL"),
+            body: Vec::new(),
+        })
+    }
+
+    /// The URLs requested so far, in order
+    pub fn requests(&self) -> Vec<Url> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn respond(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        self.requests.lock().unwrap().push(url.clone());
+        Ok(self
+            .responses
+            .get(url)
+            .cloned()
+            .unwrap_or_else(|| FetchResponse {
+                status: 404,
+                headers: Vec::new(),
+                url: url.clone(),
+                body: Vec::new(),
+            }))
+    }
+}
+
+impl Fetcher for MockFetcher {
+    fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        self.respond(url)
+    }
+}
+
+impl AsyncFetcher for MockFetcher {
+    fn fetch<'a>(&'a self, url: &'a Url) -> FetchFuture<'a> {
+        Box::pin(future::ready(self.respond(url)))
+    }
+}
@@ -1,20 +1,108 @@
+// https://www.rfc-editor.org/rfc/rfc9116
 // https://tools.ietf.org/html/draft-foudil-securitytxt-09
 
+pub mod armor;
+mod builder;
+mod classify;
+mod cleartext;
+mod contact;
+mod datetime;
+#[cfg(feature = "discover")]
+mod discover;
+mod document;
+mod error;
+#[cfg(feature = "discover")]
+mod fetch;
+#[cfg(feature = "openpgp")]
+mod openpgp;
+mod renew;
+#[cfg(feature = "reqwest")]
+mod reqwest_fetcher;
+#[cfg(feature = "discover")]
+mod scan;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sign;
+#[cfg(feature = "ureq")]
+mod ureq_fetcher;
+mod validate;
+mod verify;
+mod write;
+
 use chrono::prelude::*;
 use core::str::FromStr;
+use datetime::parse_datetime;
 use language_tags::LanguageTag;
-use std::error::Error;
-use std::fmt;
+use std::mem;
 use url::Url;
 
+pub use builder::{BuildError, SecurityTxtBuilder};
+pub use classify::{classify, NotSecurityTxt};
+pub use contact::ContactUri;
+#[cfg(feature = "ureq")]
+pub use discover::discover;
+#[cfg(feature = "discover")]
+pub use discover::{
+    discover_async, discover_with, Discovery, DiscoveryError, DiscoveryOptions, Location, Redirect,
+};
+pub use document::{Document, ExtensionError, Line, LineKind};
+pub use error::{Diagnostic, ErrorKind, ParseError, Severity};
+#[cfg(feature = "discover")]
+pub use fetch::{AsyncFetcher, FetchError, FetchFuture, FetchResponse, Fetcher, MockFetcher};
+#[cfg(feature = "openpgp")]
+pub use openpgp::{PgpSigner, PgpVerifier};
+pub use renew::{renew, renew_signed, RenewError, RenewPolicy};
+#[cfg(feature = "reqwest")]
+pub use reqwest_fetcher::ReqwestFetcher;
+#[cfg(feature = "discover")]
+pub use scan::{scan, scan_each, ScanOptions, ScanRow};
+pub use sign::{SignError, Signer};
+#[cfg(feature = "ureq")]
+pub use ureq_fetcher::UreqFetcher;
+pub use validate::{validate, Finding, Rule, ValidationOptions};
+pub use verify::{Fingerprint, SignatureVerifier, VerifyError};
+pub use write::{LineEnding, WriteOptions};
+
 /// The path under which security.txt MUST be placed, when served over HTTP
 pub const WELL_KNOWN_PATH: &str = "/.well-known/security.txt";
 
-#[derive(Debug, PartialEq)]
+/// The top-level path that security.txt might be placed at for legacy compatibility
+pub const LEGACY_PATH: &str = "/security.txt";
+
+/// The version of the specification to parse and validate against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpecVersion {
+    /// <https://tools.ietf.org/html/draft-foudil-securitytxt-09>
+    Draft09,
+    /// <https://www.rfc-editor.org/rfc/rfc9116>
+    #[default]
+    Rfc9116,
+}
+
+impl SpecVersion {
+    /// Whether the `Expires` field MUST be present
+    pub fn requires_expires(self) -> bool {
+        match self {
+            Self::Draft09 => false,
+            Self::Rfc9116 => true,
+        }
+    }
+
+    /// Whether the file may be looked up at `LEGACY_PATH` when it is not
+    /// found at `WELL_KNOWN_PATH`
+    pub fn allows_legacy_path(self) -> bool {
+        match self {
+            Self::Draft09 => true,
+            Self::Rfc9116 => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Field {
     Acknowledgments(Url), // Required HTTPS?
     Canonical(Url),       // Required HTTPS?
-    Contact(Url),
+    Contact(ContactUri),
     Encryption(Url),
     Expires(DateTime<FixedOffset>), // Must appear only once
     Hiring(Url),                    // Required HTTPS?
@@ -23,79 +111,363 @@ pub enum Field {
     Extension(String, String),
 }
 
-fn split_at_str(string: &str, pattern: char) -> Option<(&str, &str)> {
-    let mut split = string.splitn(2, pattern);
-    let first = split.next().unwrap();
-    match split.next() {
-        Some(second) => Some((first, second)),
-        None => None,
+/// The kinds of fields defined by the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    Acknowledgments,
+    Canonical,
+    Contact,
+    Encryption,
+    Expires,
+    Hiring,
+    Policy,
+    PreferredLanguages,
+}
+
+impl FieldKind {
+    const ALL: [Self; 8] = [
+        Self::Acknowledgments,
+        Self::Canonical,
+        Self::Contact,
+        Self::Encryption,
+        Self::Expires,
+        Self::Hiring,
+        Self::Policy,
+        Self::PreferredLanguages,
+    ];
+
+    /// The kind a field name as written, or a legacy alias of it, names
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.matches(name))
+    }
+
+    /// The canonical name of fields of this kind
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Acknowledgments => "Acknowledgments",
+            Self::Canonical => "Canonical",
+            Self::Contact => "Contact",
+            Self::Encryption => "Encryption",
+            Self::Expires => "Expires",
+            Self::Hiring => "Hiring",
+            Self::Policy => "Policy",
+            Self::PreferredLanguages => "Preferred-Languages",
+        }
+    }
+
+    /// Whether a field name as written, or a legacy alias of it, names this
+    /// kind, compared case-insensitively
+    pub(crate) fn matches(self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        let name = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(&*name, |(_, canonical)| canonical);
+        name.eq_ignore_ascii_case(self.name())
     }
 }
 
-fn parse_rfc5322_datetime(string: &str) -> chrono::ParseResult<DateTime<FixedOffset>> {
-    // TODO: See https://tools.ietf.org/html/rfc5322#section-3.3
-    DateTime::parse_from_str(string, "")
+/// Check that an extension field with the given name and value can be
+/// written as a single field line
+pub(crate) fn check_extension(name: &str, value: &str) -> Result<(), ExtensionError> {
+    let detail = if name.is_empty() {
+        "the name is empty".into()
+    } else if name.contains(|c: char| c == ':' || c.is_whitespace()) {
+        "the name contains a colon or whitespace".into()
+    } else if name.starts_with('#') {
+        "the name starts a comment".into()
+    } else if let Some(kind) = FieldKind::from_name(name) {
+        format!("{} is a field of the specification", kind.name())
+    } else if value.contains(['\r', '\n']) {
+        "the value contains a line break".into()
+    } else {
+        return Ok(());
+    };
+    Err(ExtensionError {
+        name: name.into(),
+        detail,
+    })
+}
+
+/// Legacy and misspelled field names, and the canonical names they are
+/// accepted as in lenient mode
+const ALIASES: &[(&str, &str)] = &[
+    ("acknowledgement", "Acknowledgments"),
+    ("acknowledgements", "Acknowledgments"),
+    ("acknowledgment", "Acknowledgments"),
+    ("preferred-language", "Preferred-Languages"),
+];
+
+fn parse_url(value: &str) -> Result<Url, ParseError> {
+    Url::parse(value)
+        .map_err(|error| ParseError::new(ErrorKind::InvalidUrl, 0..value.len()).with_detail(error))
+}
+
+impl Field {
+    /// Parse a single line according to the given version of the specification
+    pub fn parse_with_spec(string: &str, spec: SpecVersion) -> Result<Self, ParseError> {
+        Self::parse_line(string, spec, None)
+    }
+
+    /// Parse a single line, recovering from common mistakes and recording
+    /// them in `warnings` if given
+    fn parse_line(
+        string: &str,
+        spec: SpecVersion,
+        mut warnings: Option<&mut Vec<ParseError>>,
+    ) -> Result<Self, ParseError> {
+        let colon = string
+            .find(':')
+            .ok_or_else(|| ParseError::new(ErrorKind::MissingColon, 0..string.len()))?;
+        let name = &string[..colon];
+        let raw_value = &string[colon + 1..];
+        let value = raw_value.trim_start();
+        let value_start = colon + 1 + raw_value.len() - value.len();
+        let value = value.trim_end();
+        let locate = |error: ParseError| error.at_line(1, value_start).with_field(name);
+
+        let mut lowercase = name.to_lowercase();
+        if let Some(warnings) = &mut warnings {
+            if let Some((_, canonical)) = ALIASES.iter().find(|(alias, _)| *alias == lowercase) {
+                warnings.push(
+                    ParseError::new(ErrorKind::DeprecatedName, 0..name.len())
+                        .with_field(name)
+                        .with_detail(format!("use {} instead", canonical)),
+                );
+                lowercase = canonical.to_lowercase();
+            }
+        }
+
+        let field = match &*lowercase {
+            "acknowledgments" => parse_url(value).map(Self::Acknowledgments),
+            "canonical" => parse_url(value).map(Self::Canonical),
+            "contact" => match (ContactUri::from_str(value), warnings) {
+                (Err(error), Some(warnings)) if error.kind() == ErrorKind::InvalidUrl => {
+                    match ContactUri::recover(value) {
+                        Some(contact) => {
+                            warnings.push(locate(
+                                ParseError::new(ErrorKind::BareContact, 0..value.len())
+                                    .with_detail(format!("interpreted as {}", contact)),
+                            ));
+                            Ok(Self::Contact(contact))
+                        }
+                        None => Err(error),
+                    }
+                }
+                (result, _) => result.map(Self::Contact),
+            },
+            "encryption" => parse_url(value).map(Self::Encryption),
+            "expires" => parse_datetime(value, spec)
+                .map(Self::Expires)
+                .map_err(|reason| {
+                    ParseError::new(ErrorKind::InvalidDate, 0..value.len()).with_detail(reason)
+                }),
+            "hiring" => parse_url(value).map(Self::Hiring),
+            "policy" => parse_url(value).map(Self::Policy),
+            "preferred-languages" => value
+                .split(',')
+                .map(|s| {
+                    LanguageTag::from_str(s.trim()).map_err(|error| {
+                        ParseError::new(ErrorKind::InvalidLanguageTag, 0..value.len())
+                            .with_detail(error)
+                    })
+                })
+                .collect::<Result<_, _>>()
+                .map(Self::PreferredLanguages),
+            _ => Ok(Self::Extension(name.into(), value.into())),
+        };
+        field.map_err(locate)
+    }
+
+    /// The kind of the field, or `None` for extensions
+    pub fn kind(&self) -> Option<FieldKind> {
+        Some(match self {
+            Self::Acknowledgments(_) => FieldKind::Acknowledgments,
+            Self::Canonical(_) => FieldKind::Canonical,
+            Self::Contact(_) => FieldKind::Contact,
+            Self::Encryption(_) => FieldKind::Encryption,
+            Self::Expires(_) => FieldKind::Expires,
+            Self::Hiring(_) => FieldKind::Hiring,
+            Self::Policy(_) => FieldKind::Policy,
+            Self::PreferredLanguages(_) => FieldKind::PreferredLanguages,
+            Self::Extension(..) => return None,
+        })
+    }
+
+    /// Whether the field MUST NOT appear more than once
+    fn is_unique(&self) -> bool {
+        matches!(self, Self::Expires(_) | Self::PreferredLanguages(_))
+    }
 }
 
 impl FromStr for Field {
     type Err = ParseError;
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        if let Some((name, value)) = split_at_str(string, ':') {
-            return Ok(match &*name.to_lowercase() {
-                "acknowledgments" => Self::Acknowledgments(Url::parse(value)?),
-                "canonical" => Self::Canonical(Url::parse(value)?),
-                "contact" => Self::Contact(Url::parse(value)?),
-                "encryption" => Self::Encryption(Url::parse(value)?),
-                "expires" => Self::Expires(parse_rfc5322_datetime(value)?),
-                "hiring" => Self::Hiring(Url::parse(value)?),
-                "policy" => Self::Policy(Url::parse(value)?),
-                "preferred-languages" => {
-                    let languages = value
-                        .split(',')
-                        .map(|s| LanguageTag::from_str(s))
-                        .collect::<Result<_, _>>()?;
-                    Self::PreferredLanguages(languages)
-                }
-                _ => Self::Extension("Abc".into(), "def".into()),
-            });
-        }
-        Err(ParseError("Missing required fields".into()))
+        Self::parse_with_spec(string, SpecVersion::default())
     }
 }
 
-/// Signifies an error in the specification
-#[derive(Debug, PartialEq)]
-pub struct ParseError(String);
+/// Iterate over the 1-based line number, byte offset and contents of each line
+fn lines(string: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    let mut offset = 0;
+    string.split('\n').enumerate().map(move |(index, line)| {
+        let start = offset;
+        offset += line.len() + 1;
+        (index + 1, start, line.strip_suffix('\r').unwrap_or(line))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecurityTxt {
+    Unsigned(Vec<Field>),
+    /// An OpenPGP cleartext signed file: the exact signed text after
+    /// dash-unescaping, the fields parsed from it, and the armored signature
+    Signed(String, Vec<Field>, String),
+}
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Oh no, something bad went down")
+/// Record an error in `diagnostics` if given, or return it otherwise
+fn report(
+    diagnostics: &mut Option<&mut Vec<Diagnostic>>,
+    error: ParseError,
+) -> Result<(), ParseError> {
+    match diagnostics {
+        Some(diagnostics) => {
+            diagnostics.push(Diagnostic::error(error));
+            Ok(())
+        }
+        None => Err(error),
     }
 }
 
-impl Error for ParseError {}
+/// Parse the fields from the given lines, collecting problems into
+/// `diagnostics` if given, or aborting on the first error otherwise
+fn parse_fields<'a>(
+    lines: impl IntoIterator<Item = (usize, usize, &'a str)>,
+    spec: SpecVersion,
+    diagnostics: &mut Option<&mut Vec<Diagnostic>>,
+) -> Result<Vec<Field>, ParseError> {
+    let mut fields: Vec<Field> = Vec::new();
+    for (number, offset, line) in lines {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut warnings = Vec::new();
+        let lenient = diagnostics.is_some();
+        let field = Field::parse_line(line, spec, lenient.then_some(&mut warnings));
+        if let Some(diagnostics) = diagnostics {
+            diagnostics.extend(
+                warnings
+                    .into_iter()
+                    .map(|warning| Diagnostic::warning(warning.at_line(number, offset))),
+            );
+        }
+        let field = match field {
+            Ok(field) => field,
+            Err(error) => {
+                report(diagnostics, error.at_line(number, offset))?;
+                continue;
+            }
+        };
+        if field.is_unique()
+            && fields
+                .iter()
+                .any(|other| mem::discriminant(other) == mem::discriminant(&field))
+        {
+            let name = &line[..line.find(':').unwrap_or(0)];
+            report(
+                diagnostics,
+                ParseError::new(ErrorKind::DuplicateField, 0..line.len())
+                    .at_line(number, offset)
+                    .with_field(name),
+            )?;
+            continue;
+        }
+        fields.push(field);
+    }
+    Ok(fields)
+}
 
-impl From<language_tags::Error> for ParseError {
-    fn from(error: language_tags::Error) -> Self {
-        ParseError(error.to_string())
+/// Parse a complete file, which may be signed
+fn parse_document(
+    string: &str,
+    spec: SpecVersion,
+    mut diagnostics: Option<&mut Vec<Diagnostic>>,
+) -> Result<SecurityTxt, ParseError> {
+    if !cleartext::is_signed(string) {
+        return parse_fields(lines(string), spec, &mut diagnostics).map(SecurityTxt::Unsigned);
     }
+    let cleartext = cleartext::split(string);
+    for error in cleartext.errors {
+        report(&mut diagnostics, error)?;
+    }
+    let fields = parse_fields(cleartext.lines, spec, &mut diagnostics)?;
+    Ok(match cleartext.signature {
+        Some(signature) => SecurityTxt::Signed(cleartext.text, fields, signature.into()),
+        None => SecurityTxt::Unsigned(fields),
+    })
 }
 
-impl From<url::ParseError> for ParseError {
-    fn from(error: url::ParseError) -> Self {
-        ParseError(error.to_string())
+impl FromStr for SecurityTxt {
+    type Err = ParseError;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_spec(string, SpecVersion::default())
     }
 }
 
-impl From<chrono::format::ParseError> for ParseError {
-    fn from(error: chrono::format::ParseError) -> Self {
-        ParseError(error.to_string())
+impl SecurityTxt {
+    /// Parse a complete file according to the given version of the specification
+    pub fn parse_with_spec(string: &str, spec: SpecVersion) -> Result<Self, ParseError> {
+        parse_document(string, spec, None)
+    }
+
+    /// Parse a complete file without aborting on errors
+    ///
+    /// Lines that could not be parsed are skipped, and reported alongside any
+    /// warnings in the returned diagnostics.
+    pub fn parse_with_diagnostics(string: &str, spec: SpecVersion) -> (Self, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let security_txt = parse_document(string, spec, Some(&mut diagnostics))
+            .expect("errors are collected into the diagnostics");
+        (security_txt, diagnostics)
+    }
+
+    /// The fields in the order they appeared in the file
+    pub fn fields(&self) -> &[Field] {
+        match self {
+            Self::Unsigned(fields) | Self::Signed(_, fields, _) => fields,
+        }
+    }
+
+    /// The values of all extension fields with the given name, compared
+    /// case-insensitively
+    pub fn extensions<'a, 'b>(&'a self, name: &'b str) -> impl Iterator<Item = &'a str> + 'b
+    where
+        'a: 'b,
+    {
+        self.fields().iter().filter_map(move |field| match field {
+            Field::Extension(field_name, value) if field_name.eq_ignore_ascii_case(name) => {
+                Some(&**value)
+            }
+            _ => None,
+        })
+    }
+
+    /// The value of the first extension field with the given name, compared
+    /// case-insensitively
+    pub fn extension(&self, name: &str) -> Option<&str> {
+        self.extensions(name).next()
     }
 }
 
-pub enum SecurityTxt {
-    Unsigned(Vec<Field>),
-    Signed(String, Vec<Field>, String),
+/// Parse a complete security.txt file
+pub fn parse(string: &str) -> Result<SecurityTxt, ParseError> {
+    SecurityTxt::from_str(string)
+}
+
+/// Parse a complete security.txt file, reporting every problem instead of
+/// aborting on the first one
+pub fn parse_with_diagnostics(string: &str) -> (SecurityTxt, Vec<Diagnostic>) {
+    SecurityTxt::parse_with_diagnostics(string, SpecVersion::default())
 }
 
 #[cfg(test)]
@@ -110,4 +482,149 @@ mod tests {
             Field::from_str("Acknowledgments:https://abc.com")
         );
     }
+
+    #[test]
+    fn expires_format_depends_on_spec() {
+        let rfc3339 = "Expires: 2021-12-31T18:37:07Z";
+        let rfc5322 = "Expires: Fri, 31 Dec 2021 18:37:07 +0000";
+        assert!(Field::from_str(rfc3339).is_ok());
+        assert!(Field::from_str(rfc5322).is_err());
+        assert_eq!(
+            Field::from_str(rfc3339),
+            Field::parse_with_spec(rfc5322, SpecVersion::Draft09)
+        );
+    }
+
+    #[test]
+    fn extension_keeps_name_and_value() {
+        assert_eq!(
+            Ok(Field::Extension(
+                "OpenBugBounty".into(),
+                "https://openbugbounty.org/bugbounty/example/".into()
+            )),
+            Field::from_str("OpenBugBounty: https://openbugbounty.org/bugbounty/example/ ")
+        );
+    }
+
+    #[test]
+    fn extension_lookup_is_case_insensitive() {
+        let security_txt = parse("CSAF: https://example.com/provider-metadata.json\n").unwrap();
+        assert_eq!(
+            security_txt.extension("csaf"),
+            Some("https://example.com/provider-metadata.json")
+        );
+        assert_eq!(security_txt.extension("Contact"), None);
+    }
+
+    #[test]
+    fn error_kinds_and_spans() {
+        let error = parse("Contact: mailto:a@b.com\r\nPolicy: Invalid\r\n").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidUrl);
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.span(), 33..40);
+        assert_eq!(error.field(), Some("Policy"));
+        assert_eq!(
+            error.to_string(),
+            "line 2: invalid URL in Policy field: relative URL without a base"
+        );
+
+        let error = parse("# Comment\nInvalid\n").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MissingColon);
+        assert_eq!(
+            (error.line(), error.span(), error.field()),
+            (2, 10..17, None)
+        );
+
+        let error = Field::from_str("Preferred-Languages: en, 1").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidLanguageTag);
+        assert_eq!(error.span(), 21..26);
+
+        let error = Field::from_str("expires: tomorrow").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidDate);
+        assert_eq!(error.field(), Some("expires"));
+    }
+
+    #[test]
+    fn duplicate_fields() {
+        let error =
+            parse("Expires: 2021-12-31T18:37:07Z\nExpires: 2022-12-31T18:37:07Z").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::DuplicateField);
+        assert_eq!((error.line(), error.span()), (2, 30..59));
+        assert!(parse("Contact: mailto:a@b.com\nContact: mailto:c@d.com").is_ok());
+    }
+
+    #[test]
+    fn diagnostics_do_not_abort() {
+        let (security_txt, diagnostics) = parse_with_diagnostics(
+            "Expires: 2021-12-31T18:37:07Z\nContact: Invalid\nExpires: 2022-12-31T18:37:07Z\n",
+        );
+        assert_eq!(
+            security_txt.fields(),
+            &[Field::Expires(
+                DateTime::parse_from_rfc3339("2021-12-31T18:37:07Z").unwrap()
+            )]
+        );
+        let kinds: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.error.kind()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (Severity::Error, ErrorKind::InvalidUrl),
+                (Severity::Error, ErrorKind::DuplicateField)
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_contacts_are_recovered() {
+        let input = "Contact: a@b.com\nContact: +1 201 555 0123\n";
+        assert_eq!(parse(input).unwrap_err().kind(), ErrorKind::InvalidUrl);
+        let (security_txt, diagnostics) = parse_with_diagnostics(input);
+        assert_eq!(
+            security_txt.to_string(),
+            "Contact: mailto:a@b.com\nContact: tel:+1-201-555-0123\n"
+        );
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning: line 1: contact is not a URI in Contact field: interpreted as mailto:a@b.com"
+        );
+        assert_eq!(diagnostics[1].error.span(), 26..41);
+
+        let error = parse("Contact: mailto:invalid").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidContact);
+    }
+
+    #[test]
+    fn aliases_are_recovered() {
+        let input = "Acknowledgements: https://b.com/thanks\nPreferred-Language: en\n";
+        assert_eq!(
+            parse(input).unwrap().extension("acknowledgements"),
+            Some("https://b.com/thanks")
+        );
+        let (security_txt, diagnostics) = parse_with_diagnostics(input);
+        assert_eq!(
+            security_txt.to_string(),
+            "Acknowledgments: https://b.com/thanks\nPreferred-Languages: en\n"
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "warning: line 1: deprecated field name in Acknowledgements field: \
+             use Acknowledgments instead"
+        );
+        assert_eq!(diagnostics[1].error.span(), 39..57);
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        assert_eq!(
+            Ok(SecurityTxt::Unsigned(vec![
+                Field::Contact(ContactUri::Email(Url::parse("mailto:a@b.com").unwrap())),
+                Field::Policy(Url::parse("https://b.com/policy").unwrap()),
+            ])),
+            parse("# Comment\r\nContact: mailto:a@b.com\r\n\r\nPolicy: https://b.com/policy\r\n")
+        );
+    }
 }
@@ -0,0 +1,236 @@
+#[cfg(feature = "discover")]
+use crate::Fetcher;
+use crate::{Field, Fingerprint, SecurityTxt, SignError, SignatureVerifier, Signer, VerifyError};
+use pgp::composed::{Deserializable, DetachedSignature, SignedPublicKey, SignedSecretKey};
+use pgp::crypto::hash::HashAlgorithm;
+use pgp::packet::Signature;
+use pgp::ser::Serialize;
+use pgp::types::{KeyDetails, Password, SigningKey, VerifyingKey};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+fn invalid_key(error: impl ToString) -> VerifyError {
+    VerifyError::InvalidKey(error.to_string())
+}
+
+/// A `SignatureVerifier` backed by a local keyring of OpenPGP public keys
+#[derive(Debug, Clone)]
+pub struct PgpVerifier {
+    keys: Vec<SignedPublicKey>,
+}
+
+impl PgpVerifier {
+    /// Load one or more ASCII-armored public keys
+    pub fn from_armored(string: &str) -> Result<Self, VerifyError> {
+        let (keys, _) = SignedPublicKey::from_string_many(string).map_err(invalid_key)?;
+        let keys = keys.collect::<Result<Vec<_>, _>>().map_err(invalid_key)?;
+        if keys.is_empty() {
+            return Err(invalid_key("no public keys found"));
+        }
+        Ok(Self { keys })
+    }
+
+    /// Load one or more ASCII-armored public keys from a file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, VerifyError> {
+        Self::from_armored(&fs::read_to_string(path).map_err(invalid_key)?)
+    }
+
+    /// The fingerprints of the primary keys in the keyring
+    pub fn fingerprints(&self) -> impl Iterator<Item = Fingerprint> + '_ {
+        self.keys
+            .iter()
+            .map(|key| Fingerprint::new(key.primary_key.fingerprint().as_bytes()))
+    }
+}
+
+/// Whether `key` made `signature` over `text`, or `None` if the signature
+/// does not name it as the issuer
+fn check(signature: &DetachedSignature, key: &impl VerifyingKey, text: &[u8]) -> Option<bool> {
+    let issued = signature
+        .signature
+        .issuer_fingerprint()
+        .contains(&&key.fingerprint())
+        || signature
+            .signature
+            .issuer_key_id()
+            .contains(&&key.legacy_key_id());
+    if issued {
+        Some(signature.verify(key, text).is_ok())
+    } else {
+        None
+    }
+}
+
+impl SignatureVerifier for PgpVerifier {
+    fn verify(&self, text: &[u8], signature: &[u8]) -> Result<Fingerprint, VerifyError> {
+        let signature = DetachedSignature::from_bytes(signature)
+            .map_err(|error| VerifyError::InvalidSignature(error.to_string()))?;
+        let mut known = false;
+        for key in &self.keys {
+            let results = std::iter::once(check(&signature, &key.primary_key, text)).chain(
+                key.public_subkeys
+                    .iter()
+                    .map(|subkey| check(&signature, &subkey.key, text)),
+            );
+            for result in results.flatten() {
+                known = true;
+                if result {
+                    return Ok(Fingerprint::new(key.primary_key.fingerprint().as_bytes()));
+                }
+            }
+        }
+        Err(if known {
+            VerifyError::BadSignature
+        } else {
+            VerifyError::UnknownSigner
+        })
+    }
+}
+
+impl SecurityTxt {
+    /// The URLs of the `Encryption` fields
+    fn encryption_urls(&self) -> impl Iterator<Item = &Url> {
+        self.fields().iter().filter_map(|field| match field {
+            Field::Encryption(url) => Some(url),
+            _ => None,
+        })
+    }
+
+    /// Verify that the file was signed by a key published in one of its own
+    /// `Encryption` fields, given the ASCII-armored keys at those URLs
+    ///
+    /// Keys at other URLs are ignored, so a signature by them is reported as
+    /// `UnknownSigner`.
+    pub fn verify_published(
+        &self,
+        keys: &HashMap<Url, String>,
+    ) -> Result<Fingerprint, VerifyError> {
+        let published: Vec<_> = self
+            .encryption_urls()
+            .filter_map(|url| keys.get(url))
+            .map(|key| PgpVerifier::from_armored(key))
+            .collect::<Result<_, _>>()?;
+        if published.is_empty() {
+            return Err(VerifyError::NoPublishedKey);
+        }
+        let verifier = PgpVerifier {
+            keys: published
+                .into_iter()
+                .flat_map(|verifier| verifier.keys)
+                .collect(),
+        };
+        self.verify(&verifier)
+    }
+
+    /// Verify that the file was signed by a key published in one of its own
+    /// `Encryption` fields, fetching the keys at their HTTPS URLs
+    #[cfg(feature = "discover")]
+    pub fn verify_published_with<F>(&self, fetcher: &F) -> Result<Fingerprint, VerifyError>
+    where
+        F: Fetcher + ?Sized,
+    {
+        let mut keys = HashMap::new();
+        for url in self.encryption_urls().filter(|url| url.scheme() == "https") {
+            let fetched = fetcher
+                .fetch(url)
+                .map_err(|error| error.to_string())
+                .and_then(|response| match response.status {
+                    200 => String::from_utf8(response.body).map_err(|error| error.to_string()),
+                    status => Err(format!("HTTP status {}", status)),
+                });
+            match fetched {
+                Ok(key) => keys.insert(url.clone(), key),
+                Err(detail) => {
+                    let detail = format!("could not fetch {}: {}", url, detail);
+                    return Err(VerifyError::InvalidKey(detail));
+                }
+            };
+        }
+        self.verify_published(&keys)
+    }
+}
+
+/// Whether any of the binding signatures allows the key to make signatures
+fn can_sign<'a>(mut signatures: impl Iterator<Item = &'a Signature>) -> bool {
+    signatures.any(|signature| signature.key_flags().sign())
+}
+
+/// A `Signer` backed by an OpenPGP secret key
+///
+/// Signatures are made with SHA256 by the primary key if it may sign, or
+/// otherwise by the first subkey that may.
+#[derive(Debug)]
+pub struct PgpSigner {
+    key: SignedSecretKey,
+    /// The index of the signing subkey, or `None` for the primary key
+    subkey: Option<usize>,
+    passphrase: Password,
+}
+
+impl PgpSigner {
+    /// Load an ASCII-armored secret key
+    pub fn from_armored(string: &str) -> Result<Self, SignError> {
+        let (key, _) = SignedSecretKey::from_string(string).map_err(SignError::new)?;
+        key.verify_bindings().map_err(SignError::new)?;
+        let details = &key.details;
+        let primary = details
+            .users
+            .iter()
+            .flat_map(|user| &user.signatures)
+            .chain(&details.direct_signatures);
+        let subkey = if can_sign(primary) {
+            None
+        } else {
+            let subkey = key
+                .secret_subkeys
+                .iter()
+                .position(|subkey| can_sign(subkey.signatures.iter()))
+                .ok_or_else(|| SignError::new("the key may not make signatures"))?;
+            Some(subkey)
+        };
+        Ok(Self {
+            key,
+            subkey,
+            passphrase: Password::empty(),
+        })
+    }
+
+    /// Load an ASCII-armored secret key from a file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SignError> {
+        Self::from_armored(&fs::read_to_string(path).map_err(SignError::new)?)
+    }
+
+    /// Unlock the key with a passphrase
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = passphrase.into();
+        self
+    }
+
+    /// The fingerprint of the primary key
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(self.key.primary_key.fingerprint().as_bytes())
+    }
+
+    fn signing_key(&self) -> &dyn SigningKey {
+        match self.subkey {
+            Some(index) => &self.key.secret_subkeys[index].key,
+            None => &self.key.primary_key,
+        }
+    }
+}
+
+impl Signer for PgpSigner {
+    fn sign(&self, text: &[u8]) -> Result<Vec<u8>, SignError> {
+        let signature = DetachedSignature::sign_text_data(
+            rand::thread_rng(),
+            &Box::new(self.signing_key()),
+            &self.passphrase,
+            HashAlgorithm::Sha256,
+            text,
+        )
+        .map_err(SignError::new)?;
+        signature.to_bytes().map_err(SignError::new)
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{Document, Field, SignError, Signer, SpecVersion};
+use chrono::{DateTime, Duration, DurationRound, FixedOffset, Utc};
+use std::error::Error;
+use std::fmt;
+
+/// How to renew the `Expires` field of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenewPolicy {
+    /// How far past `now` to set the `Expires` field, which is capped below
+    /// one year as recommended by RFC 9116
+    pub window: Duration,
+    /// Renew the file even if it has already expired
+    pub force: bool,
+    /// The time to renew from
+    pub now: DateTime<Utc>,
+}
+
+impl Default for RenewPolicy {
+    fn default() -> Self {
+        Self {
+            window: Duration::days(180),
+            force: false,
+            now: Utc::now(),
+        }
+    }
+}
+
+/// Signifies that a file was not renewed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenewError {
+    /// The file expired at the given time, and the policy does not force
+    /// renewal
+    Expired(DateTime<FixedOffset>),
+    /// The file is signed, so renewing it requires a signer
+    SignerRequired,
+    /// The renewed file could not be signed
+    Sign(SignError),
+}
+
+impl fmt::Display for RenewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Expired(expires) => write!(f, "the file expired at {}", expires.to_rfc3339()),
+            Self::SignerRequired => write!(f, "the file is signed, so it must be signed again"),
+            Self::Sign(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for RenewError {}
+
+/// The longest window allowed, which keeps `Expires` less than a year away
+fn max_window() -> Duration {
+    Duration::days(365) - Duration::days(1)
+}
+
+/// Set the `Expires` field from the policy, and return the new date
+fn set_expires(
+    document: &mut Document,
+    policy: &RenewPolicy,
+) -> Result<DateTime<FixedOffset>, RenewError> {
+    // The most permissive version, so that dates in either format are read
+    let (security_txt, _) = document.to_security_txt_with_diagnostics(SpecVersion::Draft09);
+    let expires = security_txt.fields().iter().find_map(|field| match field {
+        Field::Expires(expires) => Some(*expires),
+        _ => None,
+    });
+    match expires {
+        Some(expires) if expires < policy.now && !policy.force => {
+            return Err(RenewError::Expired(expires));
+        }
+        _ => {}
+    }
+    let expires = policy.now + policy.window.min(max_window());
+    let expires = expires
+        .duration_trunc(Duration::seconds(1))
+        .unwrap_or(expires)
+        .fixed_offset();
+    document.set_expires(expires);
+    Ok(expires)
+}
+
+/// Push the `Expires` field of an unsigned file forward, and return the new
+/// date
+///
+/// Signed files are refused, since their signature would no longer match;
+/// use `renew_signed` for those.
+pub fn renew(
+    document: &mut Document,
+    policy: &RenewPolicy,
+) -> Result<DateTime<FixedOffset>, RenewError> {
+    if document.is_signed() {
+        return Err(RenewError::SignerRequired);
+    }
+    set_expires(document, policy)
+}
+
+/// Push the `Expires` field of a file forward, and sign it with `signer`,
+/// replacing any existing signature
+pub fn renew_signed<S: Signer + ?Sized>(
+    document: &mut Document,
+    policy: &RenewPolicy,
+    signer: &S,
+) -> Result<DateTime<FixedOffset>, RenewError> {
+    let mut renewed = document.clone();
+    renewed.strip_signature();
+    let expires = set_expires(&mut renewed, policy)?;
+    renewed.sign(signer).map_err(RenewError::Sign)?;
+    *document = renewed;
+    Ok(expires)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::armor::{Armor, Kind};
+    use crate::{cleartext, SecurityTxt};
+    use std::cell::RefCell;
+
+    /// Makes a signature packet of version 4 with SHA256, recording the text
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<Vec<u8>>>);
+
+    impl Signer for Recorder {
+        fn sign(&self, text: &[u8]) -> Result<Vec<u8>, SignError> {
+            self.0.borrow_mut().push(text.into());
+            Ok(vec![0xC2, 4, 4, 1, 1, 8])
+        }
+    }
+
+    fn policy(now: &str) -> RenewPolicy {
+        RenewPolicy {
+            window: Duration::days(90),
+            force: false,
+            now: DateTime::parse_from_rfc3339(now).unwrap().to_utc(),
+        }
+    }
+
+    #[test]
+    fn unsigned() {
+        let input =
+            "# Renewed by a robot\nContact: mailto:a@b.com\nExpires: 2030-01-01T00:00:00Z\n";
+        let mut document = Document::parse(input);
+        let expires = renew(&mut document, &policy("2029-12-01T12:34:56.789Z")).unwrap();
+        assert_eq!(expires.to_rfc3339(), "2030-03-01T12:34:56+00:00");
+        assert_eq!(
+            document.to_string(),
+            "# Renewed by a robot\nContact: mailto:a@b.com\nExpires: 2030-03-01T12:34:56Z\n"
+        );
+
+        let mut policy = policy("2031-01-01T00:00:00Z");
+        assert_eq!(
+            renew(&mut document, &policy),
+            Err(RenewError::Expired(expires))
+        );
+        policy.force = true;
+        policy.window = Duration::days(1000);
+        let expires = renew(&mut document, &policy).unwrap();
+        assert_eq!(expires.to_rfc3339(), "2031-12-31T00:00:00+00:00");
+
+        let mut document = Document::parse("Contact: mailto:a@b.com");
+        renew(&mut document, &policy).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "Contact: mailto:a@b.com\nExpires: 2031-12-31T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn signed() {
+        let input = include_str!("../tests/files/signed.txt");
+        let mut document = Document::parse(input);
+        let policy = policy("2030-06-01T00:00:00Z");
+        assert_eq!(
+            renew(&mut document, &policy),
+            Err(RenewError::SignerRequired)
+        );
+
+        let signer = Recorder::default();
+        renew_signed(&mut document, &policy, &signer).unwrap();
+        assert!(!document.is_signature_invalidated());
+        let output = document.to_string();
+        let expected_text = input
+            .lines()
+            .skip(3)
+            .take_while(|line| *line != cleartext::BEGIN_SIGNATURE)
+            .map(|line| match line.strip_prefix("Expires:") {
+                Some(_) => "Expires: 2030-08-30T00:00:00Z",
+                None => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        assert_eq!(signer.0.borrow().as_slice(), [expected_text.into_bytes()]);
+        assert!(output.starts_with("-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\n#"));
+        assert!(output.ends_with(&format!(
+            "Policy: https://example.com/security-policy.html\n{}\n",
+            Armor::new(Kind::Signature, [0xC2, 4, 4, 1, 1, 8])
+        )));
+        match SecurityTxt::parse_with_spec(&output, SpecVersion::Rfc9116).unwrap() {
+            SecurityTxt::Signed(text, _, _) => {
+                assert_eq!(
+                    cleartext::canonicalize(&text).as_bytes(),
+                    &signer.0.borrow()[0]
+                )
+            }
+            SecurityTxt::Unsigned(_) => panic!("expected a signed file"),
+        }
+
+        // Dash-escaping and line endings are kept through re-signing
+        let mut document = Document::parse("-Dashed: value\r\nContact: mailto:a@b.com");
+        renew_signed(&mut document, &policy, &signer).unwrap();
+        let output = document.to_string();
+        assert!(output.contains("\r\n\r\n- -Dashed: value\r\nContact: mailto:a@b.com\r\n"));
+        renew_signed(&mut document, &policy, &signer).unwrap();
+        assert_eq!(document.to_string(), output);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::fetch::{BODY_LIMIT, CONNECT_TIMEOUT, TIMEOUT};
+use crate::{AsyncFetcher, FetchError, FetchFuture, FetchResponse};
+use url::Url;
+
+/// Fetches with an asynchronous [reqwest](https://github.com/seanmonstar/reqwest) client
+#[derive(Debug, Clone)]
+pub struct ReqwestFetcher {
+    client: reqwest::Client,
+    body_limit: u64,
+}
+
+impl ReqwestFetcher {
+    /// A fetcher with a default client that does not follow redirects, and
+    /// gives up after 10 seconds connecting or 30 seconds in all
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(TIMEOUT)
+            .build()
+            .expect("the default client can be built");
+        Self::with_client(client)
+    }
+
+    /// A fetcher using the given client, which should not follow redirects
+    /// and should have timeouts
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            body_limit: BODY_LIMIT,
+        }
+    }
+
+    /// Fail requests whose response body is larger than `bytes`, which is
+    /// 1 MiB by default
+    pub fn body_limit(mut self, bytes: u64) -> Self {
+        self.body_limit = bytes;
+        self
+    }
+}
+
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncFetcher for ReqwestFetcher {
+    fn fetch<'a>(&'a self, url: &'a Url) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let mut response = self
+                .client
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(FetchError::new)?;
+            let status = response.status().as_u16();
+            let url = response.url().clone();
+            let headers = response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (name.as_str().to_string(), value)
+                })
+                .collect();
+            let mut body = Vec::new();
+            while let Some(chunk) = response.chunk().await.map_err(FetchError::new)? {
+                if (body.len() + chunk.len()) as u64 > self.body_limit {
+                    return Err(FetchError::too_large(self.body_limit));
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(FetchResponse {
+                status,
+                headers,
+                url,
+                body,
+            })
+        })
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    discover_with, validate, Diagnostic, DiscoveryError, DiscoveryOptions, FetchError,
+    FetchResponse, Fetcher, Field, Finding, Location, SecurityTxt, ValidationOptions,
+};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// How to scan many domains
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanOptions {
+    pub discovery: DiscoveryOptions,
+    /// How many domains to scan at the same time
+    pub concurrency: usize,
+    /// The minimum time between two requests to the same host
+    pub host_interval: Duration,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            discovery: DiscoveryOptions::default(),
+            concurrency: 8,
+            host_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The result of scanning one domain
+#[derive(Debug)]
+pub struct ScanRow {
+    pub domain: String,
+    /// The URL the file was retrieved from, if one was found
+    pub url: Option<Url>,
+    pub location: Option<Location>,
+    pub signed: bool,
+    pub expires: Option<DateTime<FixedOffset>>,
+    /// The number of `Contact` fields
+    pub contacts: usize,
+    /// Problems found while parsing the file
+    pub diagnostics: Vec<Diagnostic>,
+    /// Problems with the file and how it was served
+    pub findings: Vec<Finding>,
+    /// Why no file was found
+    pub error: Option<DiscoveryError>,
+}
+
+impl ScanRow {
+    /// Whether a file was found
+    pub fn found(&self) -> bool {
+        self.url.is_some()
+    }
+
+    fn new(domain: &str, options: &ScanOptions, fetcher: &impl Fetcher) -> Self {
+        let mut row = Self {
+            domain: domain.into(),
+            url: None,
+            location: None,
+            signed: false,
+            expires: None,
+            contacts: 0,
+            diagnostics: Vec::new(),
+            findings: Vec::new(),
+            error: None,
+        };
+        let discovery = match discover_with(domain, &options.discovery, fetcher) {
+            Ok(discovery) => discovery,
+            Err(error) => {
+                row.error = Some(error);
+                return row;
+            }
+        };
+        let fields = discovery.security_txt.fields();
+        row.signed = matches!(discovery.security_txt, SecurityTxt::Signed(..));
+        row.expires = fields.iter().find_map(|field| match field {
+            Field::Expires(expires) => Some(*expires),
+            _ => None,
+        });
+        row.contacts = fields
+            .iter()
+            .filter(|field| matches!(field, Field::Contact(_)))
+            .count();
+        let validation = ValidationOptions {
+            spec: options.discovery.spec,
+            ..ValidationOptions::default()
+        };
+        row.findings = discovery.findings;
+        row.findings
+            .extend(validate(&discovery.security_txt, &validation));
+        row.diagnostics = discovery.diagnostics;
+        row.url = Some(discovery.url);
+        row.location = Some(discovery.location);
+        row
+    }
+}
+
+/// Delays requests so that each host is sent at most one per interval
+struct RateLimited<'a, F: ?Sized> {
+    fetcher: &'a F,
+    interval: Duration,
+    /// The earliest time the next request to each host may be sent
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl<F: Fetcher + ?Sized> Fetcher for RateLimited<'_, F> {
+    fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        if let Some(host) = url.host_str() {
+            let wait = {
+                let mut next = self.next.lock().unwrap();
+                let now = Instant::now();
+                let slot = next
+                    .get(host)
+                    .copied()
+                    .filter(|slot| *slot > now)
+                    .unwrap_or(now);
+                next.insert(host.into(), slot + self.interval);
+                slot - now
+            };
+            thread::sleep(wait);
+        }
+        self.fetcher.fetch(url)
+    }
+}
+
+/// Scan domains concurrently, passing each row to `report` as soon as it is
+/// done, along with the index of its domain
+pub fn scan_each<S, F, R>(domains: &[S], options: &ScanOptions, fetcher: &F, mut report: R)
+where
+    S: AsRef<str> + Sync,
+    F: Fetcher + Sync + ?Sized,
+    R: FnMut(usize, ScanRow),
+{
+    let fetcher = RateLimited {
+        fetcher,
+        interval: options.host_interval,
+        next: Mutex::new(HashMap::new()),
+    };
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, domains.len().max(1)) {
+            let sender = sender.clone();
+            let (fetcher, next) = (&fetcher, &next);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let domain = match domains.get(index) {
+                    Some(domain) => domain.as_ref().trim(),
+                    None => break,
+                };
+                let row = ScanRow::new(domain, options, fetcher);
+                if sender.send((index, row)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        for (index, row) in receiver {
+            report(index, row);
+        }
+    });
+}
+
+/// Scan domains concurrently, and return a row for each in order
+pub fn scan<S, F>(domains: &[S], options: &ScanOptions, fetcher: &F) -> Vec<ScanRow>
+where
+    S: AsRef<str> + Sync,
+    F: Fetcher + Sync + ?Sized,
+{
+    let mut rows: Vec<Option<ScanRow>> = domains.iter().map(|_| None).collect();
+    scan_each(domains, options, fetcher, |index, row| {
+        rows[index] = Some(row)
+    });
+    rows.into_iter()
+        .map(|row| row.expect("every domain is scanned"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockFetcher;
+
+    const PLAIN: &str = "text/plain; charset=utf-8";
+
+    #[test]
+    fn rows_are_in_order() {
+        let fetcher = MockFetcher::new()
+            .with_body(
+                "https://a.example/.well-known/security.txt",
+                PLAIN,
+                "Contact: mailto:a@a.example\nContact: https://a.example/\n\
+                 Expires: 2030-01-01T00:00:00Z\n",
+            )
+            .with_body(
+                "https://c.example/.well-known/security.txt",
+                PLAIN,
+                "Contact: mailto:c@c.example\n",
+            );
+        let domains = ["a.example", "b.example", " c.example "];
+        let options = ScanOptions {
+            concurrency: 2,
+            ..ScanOptions::default()
+        };
+        let rows = scan(&domains, &options, &fetcher);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                (
+                    &*row.domain,
+                    row.found(),
+                    row.contacts,
+                    row.expires.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.example", true, 2, true),
+                ("b.example", false, 0, false),
+                ("c.example", true, 1, false),
+            ]
+        );
+        assert!(matches!(rows[1].error, Some(DiscoveryError::NotFound(_))));
+        assert_eq!(rows[2].location, Some(Location::WellKnown));
+        assert!(rows[2]
+            .findings
+            .iter()
+            .any(|finding| finding.rule == crate::Rule::ExpiresMissing));
+        assert!(scan::<&str, _>(&[], &options, &fetcher).is_empty());
+    }
+
+    #[test]
+    fn hosts_are_rate_limited() {
+        let hub = "https://hub.example/security.txt";
+        let fetcher = MockFetcher::new()
+            .with_redirect("https://a.example/.well-known/security.txt", hub)
+            .with_redirect("https://b.example/.well-known/security.txt", hub)
+            .with_redirect("https://c.example/.well-known/security.txt", hub)
+            .with_body(hub, PLAIN, "Contact: mailto:a@hub.example\n");
+        let options = ScanOptions {
+            host_interval: Duration::from_millis(50),
+            ..ScanOptions::default()
+        };
+        let start = Instant::now();
+        let rows = scan(&["a.example", "b.example", "c.example"], &options, &fetcher);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert!(rows.iter().all(ScanRow::found));
+        assert_eq!(fetcher.requests().len(), 6);
+    }
+}
@@ -0,0 +1,368 @@
+//! Serde support, with URLs, contacts and language tags as strings, and
+//! `Expires` as an RFC 3339 date-time
+
+use crate::{lines, parse_fields, parse_url, ContactUri, Field, SecurityTxt, SpecVersion};
+use chrono::{DateTime, SecondsFormat};
+use language_tags::LanguageTag;
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A single field, tagged with its kind
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum FieldRepr {
+    Acknowledgments { value: String },
+    Canonical { value: String },
+    Contact { value: String },
+    Encryption { value: String },
+    Expires { value: String },
+    Hiring { value: String },
+    Policy { value: String },
+    PreferredLanguages { value: Vec<String> },
+    Extension { name: String, value: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Extension {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Signature {
+    /// The exact text that was signed
+    signed_text: String,
+    /// The armored signature
+    armor: String,
+}
+
+/// A whole file, with its fields grouped by kind
+///
+/// Every group is always serialized, even when empty, so that the schema is
+/// the same for every file.
+#[derive(Serialize, Deserialize)]
+struct SecurityTxtRepr {
+    #[serde(default)]
+    acknowledgments: Vec<String>,
+    #[serde(default)]
+    canonical: Vec<String>,
+    #[serde(default)]
+    contact: Vec<String>,
+    #[serde(default)]
+    encryption: Vec<String>,
+    #[serde(default)]
+    expires: Vec<String>,
+    #[serde(default)]
+    hiring: Vec<String>,
+    #[serde(default)]
+    policy: Vec<String>,
+    #[serde(default)]
+    preferred_languages: Vec<Vec<String>>,
+    #[serde(default)]
+    extensions: Vec<Extension>,
+    #[serde(default)]
+    signature: Option<Signature>,
+}
+
+fn expires(expires: &DateTime<chrono::FixedOffset>) -> String {
+    expires.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn languages(tags: &[LanguageTag]) -> Vec<String> {
+    tags.iter().map(ToString::to_string).collect()
+}
+
+impl From<&Field> for FieldRepr {
+    fn from(field: &Field) -> Self {
+        match field {
+            Field::Acknowledgments(url) => Self::Acknowledgments {
+                value: url.to_string(),
+            },
+            Field::Canonical(url) => Self::Canonical {
+                value: url.to_string(),
+            },
+            Field::Contact(contact) => Self::Contact {
+                value: contact.to_string(),
+            },
+            Field::Encryption(url) => Self::Encryption {
+                value: url.to_string(),
+            },
+            Field::Expires(date) => Self::Expires {
+                value: expires(date),
+            },
+            Field::Hiring(url) => Self::Hiring {
+                value: url.to_string(),
+            },
+            Field::Policy(url) => Self::Policy {
+                value: url.to_string(),
+            },
+            Field::PreferredLanguages(tags) => Self::PreferredLanguages {
+                value: languages(tags),
+            },
+            Field::Extension(name, value) => Self::Extension {
+                name: name.clone(),
+                value: value.clone(),
+            },
+        }
+    }
+}
+
+impl FieldRepr {
+    fn into_field<E: Error>(self) -> Result<Field, E> {
+        let url = |value: String| parse_url(&value).map_err(E::custom);
+        Ok(match self {
+            Self::Acknowledgments { value } => Field::Acknowledgments(url(value)?),
+            Self::Canonical { value } => Field::Canonical(url(value)?),
+            Self::Contact { value } => {
+                Field::Contact(ContactUri::from_str(&value).map_err(E::custom)?)
+            }
+            Self::Encryption { value } => Field::Encryption(url(value)?),
+            Self::Expires { value } => {
+                Field::Expires(DateTime::parse_from_rfc3339(&value).map_err(E::custom)?)
+            }
+            Self::Hiring { value } => Field::Hiring(url(value)?),
+            Self::Policy { value } => Field::Policy(url(value)?),
+            Self::PreferredLanguages { value } => Field::PreferredLanguages(
+                value
+                    .iter()
+                    .map(|tag| LanguageTag::from_str(tag).map_err(E::custom))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Self::Extension { name, value } => Field::Extension(name, value),
+        })
+    }
+}
+
+impl Serialize for Field {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        FieldRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FieldRepr::deserialize(deserializer)?.into_field()
+    }
+}
+
+/// Fields are grouped by kind, so the order of fields of different kinds is
+/// not kept, though the signed text of a signed file is.
+impl Serialize for SecurityTxt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut repr = SecurityTxtRepr {
+            acknowledgments: Vec::new(),
+            canonical: Vec::new(),
+            contact: Vec::new(),
+            encryption: Vec::new(),
+            expires: Vec::new(),
+            hiring: Vec::new(),
+            policy: Vec::new(),
+            preferred_languages: Vec::new(),
+            extensions: Vec::new(),
+            signature: match self {
+                Self::Unsigned(_) => None,
+                Self::Signed(signed_text, _, armor) => Some(Signature {
+                    signed_text: signed_text.clone(),
+                    armor: armor.clone(),
+                }),
+            },
+        };
+        for field in self.fields() {
+            match field {
+                Field::Acknowledgments(url) => repr.acknowledgments.push(url.to_string()),
+                Field::Canonical(url) => repr.canonical.push(url.to_string()),
+                Field::Contact(contact) => repr.contact.push(contact.to_string()),
+                Field::Encryption(url) => repr.encryption.push(url.to_string()),
+                Field::Expires(date) => repr.expires.push(expires(date)),
+                Field::Hiring(url) => repr.hiring.push(url.to_string()),
+                Field::Policy(url) => repr.policy.push(url.to_string()),
+                Field::PreferredLanguages(tags) => repr.preferred_languages.push(languages(tags)),
+                Field::Extension(name, value) => repr.extensions.push(Extension {
+                    name: name.clone(),
+                    value: value.clone(),
+                }),
+            }
+        }
+        repr.serialize(serializer)
+    }
+}
+
+/// Whether both lists have the same fields, in any order
+fn same_fields(fields: &[Field], others: &[Field]) -> bool {
+    let mut others: Vec<_> = others.iter().collect();
+    fields.len() == others.len()
+        && fields.iter().all(
+            |field| match others.iter().position(|other| *other == field) {
+                Some(index) => {
+                    others.swap_remove(index);
+                    true
+                }
+                None => false,
+            },
+        )
+}
+
+/// The fields of a signed file are parsed from its signed text, in order, so
+/// that they are covered by the signature. Fields given alongside it must
+/// match those, as parsed strictly or leniently according to some version of
+/// the specification.
+impl<'de> Deserialize<'de> for SecurityTxt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = SecurityTxtRepr::deserialize(deserializer)?;
+        let mut fields = Vec::new();
+        for value in repr.acknowledgments {
+            fields.push(FieldRepr::Acknowledgments { value });
+        }
+        for value in repr.canonical {
+            fields.push(FieldRepr::Canonical { value });
+        }
+        for value in repr.contact {
+            fields.push(FieldRepr::Contact { value });
+        }
+        for value in repr.encryption {
+            fields.push(FieldRepr::Encryption { value });
+        }
+        for value in repr.expires {
+            fields.push(FieldRepr::Expires { value });
+        }
+        for value in repr.hiring {
+            fields.push(FieldRepr::Hiring { value });
+        }
+        for value in repr.policy {
+            fields.push(FieldRepr::Policy { value });
+        }
+        for value in repr.preferred_languages {
+            fields.push(FieldRepr::PreferredLanguages { value });
+        }
+        for Extension { name, value } in repr.extensions {
+            fields.push(FieldRepr::Extension { name, value });
+        }
+        let fields: Vec<Field> = fields
+            .into_iter()
+            .map(FieldRepr::into_field)
+            .collect::<Result<_, _>>()?;
+        let Signature { signed_text, armor } = match repr.signature {
+            None => return Ok(Self::Unsigned(fields)),
+            Some(signature) => signature,
+        };
+        // Strict parsing first, as `parse` does, then lenient parsing, which
+        // recovers from errors and renames legacy fields
+        let text = signed_text.as_str();
+        let mut parses = [SpecVersion::Rfc9116, SpecVersion::Draft09]
+            .iter()
+            .flat_map(|&spec| {
+                let strict = parse_fields(lines(text), spec, &mut None).ok();
+                let lenient = move || {
+                    let mut diagnostics = Vec::new();
+                    parse_fields(lines(text), spec, &mut Some(&mut diagnostics))
+                        .expect("errors are collected into the diagnostics")
+                };
+                strict.into_iter().chain(std::iter::once_with(lenient))
+            });
+        let signed = if fields.is_empty() {
+            parses.next()
+        } else {
+            parses.find(|signed| same_fields(signed, &fields))
+        }
+        .ok_or_else(|| D::Error::custom("the fields do not match the signed text"))?;
+        Ok(Self::Signed(signed_text, signed, armor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field() {
+        let field = Field::from_str("Preferred-Languages: en, da").unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"preferred_languages","value":["en","da"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Field>(&json).unwrap(), field);
+
+        let field = Field::from_str("CSAF: https://example.com/provider-metadata.json").unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"extension","name":"CSAF","value":"https://example.com/provider-metadata.json"}"#
+        );
+        assert_eq!(serde_json::from_str::<Field>(&json).unwrap(), field);
+
+        let invalid = r#"{"kind":"expires","value":"2030-01-01"}"#;
+        assert!(serde_json::from_str::<Field>(invalid).is_err());
+        let invalid = r#"{"kind":"contact","value":"security@example.com"}"#;
+        assert!(serde_json::from_str::<Field>(invalid).is_err());
+    }
+
+    #[test]
+    fn security_txt() {
+        let input = "Contact: mailto:security@example.com\n\
+                     Contact: tel:+1-201-555-0123\n\
+                     Expires: 2030-12-31T23:59:59.5+01:00\n\
+                     Preferred-Languages: en, da\n\
+                     CSAF: https://example.com/provider-metadata.json\n";
+        let (security_txt, _) =
+            SecurityTxt::parse_with_diagnostics(input, crate::SpecVersion::Rfc9116);
+        let json = serde_json::to_string(&security_txt).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"acknowledgments":[],"canonical":[],"#,
+                r#""contact":["mailto:security@example.com","tel:+1-201-555-0123"],"#,
+                r#""encryption":[],"expires":["2030-12-31T23:59:59.500+01:00"],"#,
+                r#""hiring":[],"policy":[],"preferred_languages":[["en","da"]],"#,
+                r#""extensions":[{"name":"CSAF","value":"https://example.com/provider-metadata.json"}],"#,
+                r#""signature":null}"#
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<SecurityTxt>(&json).unwrap(),
+            security_txt
+        );
+
+        let signed = include_str!("../tests/files/signed.txt");
+        let security_txt = SecurityTxt::from_str(signed).unwrap();
+        let json = serde_json::to_string(&security_txt).unwrap();
+        let reparsed = serde_json::from_str::<SecurityTxt>(&json).unwrap();
+        assert_eq!(reparsed, security_txt);
+
+        // Legacy field names are kept as extensions by strict parsing, and
+        // renamed by lenient parsing
+        let alias = include_str!("../tests/files/signed-alias.txt");
+        let strict = SecurityTxt::from_str(alias).unwrap();
+        assert!(strict.fields().contains(
+            &Field::from_str("Acknowledgements: https://example.com/hall-of-fame.html").unwrap()
+        ));
+        let (lenient, _) = SecurityTxt::parse_with_diagnostics(alias, SpecVersion::default());
+        assert_ne!(lenient, strict);
+        for security_txt in [strict, lenient].iter() {
+            let json = serde_json::to_string(security_txt).unwrap();
+            let reparsed = serde_json::from_str::<SecurityTxt>(&json).unwrap();
+            assert_eq!(&reparsed, security_txt);
+        }
+
+        // Fields that are not in the signed text are rejected
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["contact"][0] = "mailto:evil@attacker.example".into();
+        assert!(serde_json::from_value::<SecurityTxt>(value.clone()).is_err());
+        value["contact"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<SecurityTxt>(value.clone()).is_err());
+        // Without any fields, they are all taken from the signed text
+        let value = serde_json::json!({ "signature": value["signature"] });
+        assert_eq!(
+            serde_json::from_value::<SecurityTxt>(value).unwrap(),
+            security_txt
+        );
+
+        assert_eq!(
+            serde_json::from_str::<SecurityTxt>(r#"{"contact":["mailto:a@b.com"]}"#).unwrap(),
+            SecurityTxt::Unsigned(vec![Field::from_str("Contact: mailto:a@b.com").unwrap()])
+        );
+        assert!(serde_json::from_str::<SecurityTxt>(r#"{"canonical":["not a URL"]}"#).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::armor::{Armor, Kind};
+use crate::{cleartext, SecurityTxt};
+use std::error::Error;
+use std::fmt;
+
+/// Signifies that a file could not be signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignError(String);
+
+impl SignError {
+    pub fn new(detail: impl fmt::Display) -> Self {
+        Self(detail.to_string())
+    }
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "could not sign the file: {}", self.0)
+    }
+}
+
+impl Error for SignError {}
+
+/// Makes OpenPGP signatures for cleartext signed files
+pub trait Signer {
+    /// Sign `text` and return the signature packet(s), not armored
+    ///
+    /// `text` is the text to sign canonicalised as specified in RFC 4880
+    /// section 7.1, so the signature must be a text signature (type 0x01).
+    fn sign(&self, text: &[u8]) -> Result<Vec<u8>, SignError>;
+}
+
+/// Sign text as in a cleartext signed message, and return the name of the
+/// hash algorithm for the `Hash` armor header and the armored signature
+pub(crate) fn sign_text<S: Signer + ?Sized>(
+    signer: &S,
+    text: &str,
+) -> Result<(&'static str, String), SignError> {
+    let signature = signer.sign(cleartext::canonicalize(text).as_bytes())?;
+    let hash = cleartext::hash_algorithm(&signature)
+        .ok_or_else(|| SignError::new("the signature has an unknown hash algorithm"))?;
+    Ok((hash, Armor::new(Kind::Signature, signature).to_string()))
+}
+
+impl SecurityTxt {
+    /// Sign the file, replacing any existing signature
+    ///
+    /// The signed text of an unsigned file is its fields, one per line.
+    pub fn sign<S: Signer + ?Sized>(&self, signer: &S) -> Result<Self, SignError> {
+        let (text, fields) = match self {
+            Self::Unsigned(fields) => {
+                let lines: Vec<_> = fields.iter().map(ToString::to_string).collect();
+                (lines.join("\n"), fields)
+            }
+            Self::Signed(text, fields, _) => (text.clone(), fields),
+        };
+        let (_, signature) = sign_text(signer, &text)?;
+        Ok(Self::Signed(text, fields.clone(), signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, LineEnding, WriteOptions};
+
+    /// Makes a signature packet of version 4 with SHA512
+    struct Fake;
+
+    impl Signer for Fake {
+        fn sign(&self, text: &[u8]) -> Result<Vec<u8>, SignError> {
+            assert_eq!(text, b"Contact: mailto:a@b.com\r\n-Dashed: value");
+            Ok(vec![0xC2, 4, 4, 1, 1, 10])
+        }
+    }
+
+    #[test]
+    fn sign_security_txt() {
+        let security_txt = parse("Contact: mailto:a@b.com \n-Dashed: value\n").unwrap();
+        let signed = security_txt.sign(&Fake).unwrap();
+        assert_eq!(signed.fields(), security_txt.fields());
+        let armor = "-----BEGIN PGP SIGNATURE-----\r\n\r\nwgQEAQEK\r\n=i5tB\r\n-----END PGP SIGNATURE-----\r\n";
+        let options = WriteOptions {
+            line_ending: LineEnding::CrLf,
+            header: None,
+        };
+        let mut output = Vec::new();
+        signed.write_with(&mut output, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "-----BEGIN PGP SIGNED MESSAGE-----\r\nHash: SHA512\r\n\r\n\
+                 Contact: mailto:a@b.com\r\n- -Dashed: value\r\n{}",
+                armor
+            )
+        );
+        assert_eq!(parse(&signed.to_string()).unwrap(), signed);
+        assert_eq!(signed.sign(&Fake).unwrap(), signed);
+    }
+
+    #[test]
+    fn unknown_hash_algorithm() {
+        struct Md2;
+        impl Signer for Md2 {
+            fn sign(&self, _: &[u8]) -> Result<Vec<u8>, SignError> {
+                Ok(vec![0xC2, 4, 4, 1, 1, 5])
+            }
+        }
+        let security_txt = SecurityTxt::Unsigned(Vec::new());
+        assert!(security_txt.sign(&Md2).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::fetch::{BODY_LIMIT, CONNECT_TIMEOUT, TIMEOUT};
+use crate::{FetchError, FetchResponse, Fetcher};
+use std::io::Read;
+use url::Url;
+
+/// Fetches with a blocking [ureq](https://github.com/algesten/ureq) agent
+#[derive(Debug, Clone)]
+pub struct UreqFetcher {
+    agent: ureq::Agent,
+    body_limit: u64,
+}
+
+impl UreqFetcher {
+    /// A fetcher with a default agent that does not follow redirects, and
+    /// gives up after 10 seconds connecting or 30 seconds in all
+    pub fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .redirects(0)
+            .timeout_connect(CONNECT_TIMEOUT)
+            .timeout(TIMEOUT)
+            .build();
+        Self::with_agent(agent)
+    }
+
+    /// A fetcher using the given agent, which should not follow redirects and
+    /// should have timeouts
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        Self {
+            agent,
+            body_limit: BODY_LIMIT,
+        }
+    }
+
+    /// Fail requests whose response body is larger than `bytes`, which is
+    /// 1 MiB by default
+    pub fn body_limit(mut self, bytes: u64) -> Self {
+        self.body_limit = bytes;
+        self
+    }
+}
+
+impl Default for UreqFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fetcher for UreqFetcher {
+    fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let response = match self.agent.request_url("GET", url).call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => return Err(FetchError::new(error)),
+        };
+        let status = response.status();
+        let url = Url::parse(response.get_url()).map_err(FetchError::new)?;
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(self.body_limit.saturating_add(1))
+            .read_to_end(&mut body)
+            .map_err(FetchError::new)?;
+        if body.len() as u64 > self.body_limit {
+            return Err(FetchError::too_large(self.body_limit));
+        }
+        Ok(FetchResponse {
+            status,
+            headers,
+            url,
+            body,
+        })
+    }
+}
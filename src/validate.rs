@@ -0,0 +1,432 @@
+use crate::{Field, SecurityTxt, Severity, SpecVersion};
+use chrono::prelude::*;
+use std::fmt;
+use url::Url;
+
+/// A requirement or recommendation of the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// The `Contact` field MUST always be present
+    ContactMissing,
+    /// The `Expires` field MUST always be present (RFC 9116 only)
+    ExpiresMissing,
+    /// The `Expires` field MUST NOT appear more than once
+    ExpiresDuplicate,
+    /// A file whose `Expires` date is in the past MUST NOT be used
+    ExpiresPast,
+    /// The `Expires` date SHOULD be less than a year into the future
+    ExpiresTooFar,
+    /// The `Preferred-Languages` field MUST NOT appear more than once
+    PreferredLanguagesDuplicate,
+    /// Web URIs MUST begin with "https://"
+    InsecureUri,
+    /// The file SHOULD be digitally signed
+    NotSigned,
+    /// A signed file SHOULD contain a `Canonical` field
+    CanonicalMissing,
+    /// The file MUST be served as `text/plain` with a UTF-8 charset
+    ContentTypeInvalid,
+    /// The file was retrieved through a redirect
+    Redirected,
+    /// A redirect to another host MUST be noted
+    CrossHostRedirect,
+    /// The URL the file was retrieved from SHOULD be one of its `Canonical`
+    /// URIs
+    CanonicalMismatch,
+}
+
+impl Rule {
+    /// A stable identifier for the rule
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::ContactMissing => "contact-missing",
+            Self::ExpiresMissing => "expires-missing",
+            Self::ExpiresDuplicate => "expires-duplicate",
+            Self::ExpiresPast => "expires-past",
+            Self::ExpiresTooFar => "expires-too-far",
+            Self::PreferredLanguagesDuplicate => "preferred-languages-duplicate",
+            Self::InsecureUri => "insecure-uri",
+            Self::NotSigned => "not-signed",
+            Self::CanonicalMissing => "canonical-missing",
+            Self::ContentTypeInvalid => "content-type-invalid",
+            Self::Redirected => "redirected",
+            Self::CrossHostRedirect => "cross-host-redirect",
+            Self::CanonicalMismatch => "canonical-mismatch",
+        }
+    }
+
+    /// How serious it is to break the rule
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::ContactMissing
+            | Self::ExpiresMissing
+            | Self::ExpiresDuplicate
+            | Self::ExpiresPast
+            | Self::PreferredLanguagesDuplicate
+            | Self::InsecureUri
+            | Self::ContentTypeInvalid => Severity::Error,
+            Self::ExpiresTooFar
+            | Self::CanonicalMissing
+            | Self::CrossHostRedirect
+            | Self::CanonicalMismatch => Severity::Warning,
+            Self::NotSigned | Self::Redirected => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// A broken rule found by `validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// The index of the offending field in `SecurityTxt::fields`, if any
+    pub field: Option<usize>,
+}
+
+impl Finding {
+    pub(crate) fn new(rule: Rule, field: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            severity: rule.severity(),
+            message: message.into(),
+            field,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// What to validate against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationOptions {
+    pub spec: SpecVersion,
+    /// The time to compare the `Expires` field against
+    pub now: DateTime<Utc>,
+    /// The URL the file was retrieved from, to compare the `Canonical`
+    /// fields against
+    pub url: Option<Url>,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            spec: SpecVersion::default(),
+            now: Utc::now(),
+            url: None,
+        }
+    }
+}
+
+/// Whether two URLs identify the same resource, ignoring trailing slashes
+/// and fragments
+///
+/// Host case, IDNA and default ports are already normalised by `Url`.
+fn same_resource(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme()
+        && a.host() == b.host()
+        && a.port_or_known_default() == b.port_or_known_default()
+        && a.username() == b.username()
+        && a.path().trim_end_matches('/') == b.path().trim_end_matches('/')
+        && a.query() == b.query()
+}
+
+/// Check that `url` is one of the `Canonical` URIs, if there are any
+pub(crate) fn check_canonical(fields: &[Field], url: &Url) -> Option<Finding> {
+    let canonical: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter_map(|(i, field)| match field {
+            Field::Canonical(canonical) => Some((i, canonical)),
+            _ => None,
+        })
+        .collect();
+    let (first, _) = canonical.first()?;
+    if canonical
+        .iter()
+        .any(|(_, canonical)| same_resource(canonical, url))
+    {
+        return None;
+    }
+    let listed: Vec<_> = canonical
+        .iter()
+        .map(|(_, canonical)| canonical.as_str())
+        .collect();
+    let message = format!(
+        "the file was retrieved from {}, which is not a Canonical URI: {}",
+        url,
+        listed.join(", ")
+    );
+    Some(Finding::new(Rule::CanonicalMismatch, Some(*first), message))
+}
+
+/// Check a file against every requirement and recommendation of the
+/// specification
+pub fn validate(security_txt: &SecurityTxt, options: &ValidationOptions) -> Vec<Finding> {
+    let fields = security_txt.fields();
+    let mut findings = Vec::new();
+
+    if !fields
+        .iter()
+        .any(|field| matches!(field, Field::Contact(_)))
+    {
+        let message = "the Contact field must be present";
+        findings.push(Finding::new(Rule::ContactMissing, None, message));
+    }
+
+    let mut expires = fields
+        .iter()
+        .enumerate()
+        .filter_map(|(i, field)| match field {
+            Field::Expires(expires) => Some((i, expires)),
+            _ => None,
+        });
+    match expires.next() {
+        Some((i, expires)) => {
+            if *expires < options.now {
+                let message = format!("the file expired at {}", expires.to_rfc3339());
+                findings.push(Finding::new(Rule::ExpiresPast, Some(i), message));
+            } else if *expires > options.now + chrono::Duration::days(365) {
+                let message = "the Expires date should be less than a year into the future";
+                findings.push(Finding::new(Rule::ExpiresTooFar, Some(i), message));
+            }
+        }
+        None if options.spec.requires_expires() => {
+            let message = "the Expires field must be present";
+            findings.push(Finding::new(Rule::ExpiresMissing, None, message));
+        }
+        None => {}
+    }
+    for (i, _) in expires {
+        let message = "the Expires field must not appear more than once";
+        findings.push(Finding::new(Rule::ExpiresDuplicate, Some(i), message));
+    }
+
+    let languages = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| matches!(field, Field::PreferredLanguages(_)));
+    for (i, _) in languages.skip(1) {
+        let message = "the Preferred-Languages field must not appear more than once";
+        findings.push(Finding::new(
+            Rule::PreferredLanguagesDuplicate,
+            Some(i),
+            message,
+        ));
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+        match field {
+            Field::Acknowledgments(url)
+            | Field::Canonical(url)
+            | Field::Encryption(url)
+            | Field::Hiring(url)
+            | Field::Policy(url)
+                if url.scheme() == "http" =>
+            {
+                let message = format!("the {} URI must begin with https://", field.name());
+                findings.push(Finding::new(Rule::InsecureUri, Some(i), message));
+            }
+            Field::Contact(contact) if contact.as_url().scheme() == "http" => {
+                let message = "the Contact URI must begin with https://";
+                findings.push(Finding::new(Rule::InsecureUri, Some(i), message));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(finding) = options
+        .url
+        .as_ref()
+        .and_then(|url| check_canonical(fields, url))
+    {
+        findings.push(finding);
+    }
+
+    match security_txt {
+        SecurityTxt::Unsigned(_) => {
+            let message = "the file should be digitally signed";
+            findings.push(Finding::new(Rule::NotSigned, None, message));
+        }
+        SecurityTxt::Signed(..) => {
+            if !fields
+                .iter()
+                .any(|field| matches!(field, Field::Canonical(_)))
+            {
+                let message = "a signed file should contain a Canonical field";
+                findings.push(Finding::new(Rule::CanonicalMissing, None, message));
+            }
+        }
+    }
+
+    findings
+}
+
+impl SecurityTxt {
+    /// Check the file against the specification, see `validate`
+    pub fn validate(&self, options: &ValidationOptions) -> Vec<Finding> {
+        validate(self, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn rules(input: &str, spec: SpecVersion) -> Vec<(Rule, Option<usize>)> {
+        let options = ValidationOptions {
+            spec,
+            now: DateTime::parse_from_rfc3339("2021-01-01T00:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            url: None,
+        };
+        validate(&parse(input).unwrap(), &options)
+            .into_iter()
+            .map(|finding| (finding.rule, finding.field))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let input = "Contact: mailto:a@b.com\nExpires: 2021-06-01T00:00:00Z\n";
+        assert_eq!(
+            rules(input, SpecVersion::Rfc9116),
+            vec![(Rule::NotSigned, None)]
+        );
+    }
+
+    #[test]
+    fn required_fields() {
+        assert_eq!(
+            rules("", SpecVersion::Rfc9116),
+            vec![
+                (Rule::ContactMissing, None),
+                (Rule::ExpiresMissing, None),
+                (Rule::NotSigned, None),
+            ]
+        );
+        assert_eq!(
+            rules("", SpecVersion::Draft09),
+            vec![(Rule::ContactMissing, None), (Rule::NotSigned, None)]
+        );
+    }
+
+    #[test]
+    fn expires() {
+        let past = "Contact: mailto:a@b.com\nExpires: 2020-12-31T23:59:59Z\n";
+        assert_eq!(
+            rules(past, SpecVersion::Rfc9116)[0],
+            (Rule::ExpiresPast, Some(1))
+        );
+        let far = "Contact: mailto:a@b.com\nExpires: 2022-06-01T00:00:00Z\n";
+        assert_eq!(
+            rules(far, SpecVersion::Rfc9116)[0],
+            (Rule::ExpiresTooFar, Some(1))
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let expires = DateTime::parse_from_rfc3339("2021-06-01T00:00:00Z").unwrap();
+        let security_txt = SecurityTxt::Unsigned(vec![
+            Field::Contact("mailto:a@b.com".parse().unwrap()),
+            Field::Expires(expires),
+            Field::PreferredLanguages(vec!["en".parse().unwrap()]),
+            Field::Expires(expires),
+            Field::PreferredLanguages(vec!["da".parse().unwrap()]),
+        ]);
+        let options = ValidationOptions {
+            now: expires.with_timezone(&Utc) - chrono::Duration::days(1),
+            ..ValidationOptions::default()
+        };
+        let findings: Vec<_> = validate(&security_txt, &options)
+            .into_iter()
+            .map(|finding| (finding.rule.id(), finding.severity, finding.field))
+            .collect();
+        assert_eq!(
+            findings,
+            vec![
+                ("expires-duplicate", Severity::Error, Some(3)),
+                ("preferred-languages-duplicate", Severity::Error, Some(4)),
+                ("not-signed", Severity::Info, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn insecure_uri() {
+        let input =
+            "Contact: http://example.com\nContact: mailto:a@b.com\nCSAF: http://example.com\n";
+        assert_eq!(
+            rules(input, SpecVersion::Draft09),
+            vec![(Rule::InsecureUri, Some(0)), (Rule::NotSigned, None)]
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let input = "Canonical: https://example.com/.well-known/security.txt\n\
+                     Canonical: https://xn--bcher-kva.example/security.txt/\n";
+        let security_txt = parse(input).unwrap();
+        let fields = security_txt.fields();
+        let matches = [
+            "https://example.com/.well-known/security.txt",
+            "https://EXAMPLE.com:443/.well-known/security.txt#top",
+            "https://Bücher.example/security.txt",
+            "https://xn--bcher-kva.example:443/security.txt",
+        ];
+        for url in matches.iter() {
+            assert_eq!(
+                check_canonical(fields, &url.parse().unwrap()),
+                None,
+                "{}",
+                url
+            );
+        }
+        let mismatches = [
+            "http://example.com/.well-known/security.txt",
+            "https://example.com:8443/.well-known/security.txt",
+            "https://www.example.com/.well-known/security.txt",
+            "https://example.com/security.txt",
+        ];
+        for url in mismatches.iter() {
+            let finding = check_canonical(fields, &url.parse().unwrap()).unwrap();
+            assert_eq!(
+                (finding.rule, finding.field),
+                (Rule::CanonicalMismatch, Some(0))
+            );
+            assert!(finding.message.contains(url));
+            assert!(finding
+                .message
+                .contains("https://xn--bcher-kva.example/security.txt/"));
+        }
+
+        let options = ValidationOptions {
+            url: Some(
+                "https://example.org/.well-known/security.txt"
+                    .parse()
+                    .unwrap(),
+            ),
+            ..ValidationOptions::default()
+        };
+        assert!(validate(&security_txt, &options)
+            .iter()
+            .any(|finding| finding.rule == Rule::CanonicalMismatch));
+        assert_eq!(
+            check_canonical(&[], &"https://example.org/".parse().unwrap()),
+            None
+        );
+    }
+}
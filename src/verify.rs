@@ -0,0 +1,153 @@
+use crate::armor::{Armor, Kind};
+use crate::{cleartext, SecurityTxt};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The fingerprint of an OpenPGP key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint(Vec<u8>);
+
+impl Fingerprint {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = VerifyError;
+    /// Parse a hexadecimal fingerprint, ignoring whitespace
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let digits: Vec<u8> = string
+            .bytes()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        if digits.is_empty() || !digits.len().is_multiple_of(2) {
+            return Err(VerifyError::InvalidKey("invalid fingerprint length".into()));
+        }
+        digits
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| VerifyError::InvalidKey("invalid fingerprint".into()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+/// Signifies that the signature of a file could not be verified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The file is not signed
+    NotSigned,
+    /// The ASCII armor around the signature is malformed
+    InvalidArmor(String),
+    /// The signature could not be decoded
+    InvalidSignature(String),
+    /// A key supplied to the verifier could not be used
+    InvalidKey(String),
+    /// None of the known keys made the signature
+    UnknownSigner,
+    /// None of the `Encryption` fields of the file point to a known key
+    NoPublishedKey,
+    /// The signature does not match the signed text
+    BadSignature,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotSigned => write!(f, "the file is not signed"),
+            Self::InvalidArmor(detail) => write!(f, "invalid signature armor: {}", detail),
+            Self::InvalidSignature(detail) => write!(f, "invalid signature: {}", detail),
+            Self::InvalidKey(detail) => write!(f, "invalid key: {}", detail),
+            Self::UnknownSigner => write!(f, "the signature was not made by a known key"),
+            Self::NoPublishedKey => write!(f, "no key is published in an Encryption field"),
+            Self::BadSignature => write!(f, "the signature does not match the signed text"),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Verifies OpenPGP signatures on cleartext signed files
+pub trait SignatureVerifier {
+    /// Verify that `signature` is a valid signature over `text`, and return
+    /// the fingerprint of the primary key that made it
+    ///
+    /// `text` is the signed text canonicalised as specified in RFC 4880
+    /// section 7.1, and `signature` is the dearmored signature packet(s).
+    fn verify(&self, text: &[u8], signature: &[u8]) -> Result<Fingerprint, VerifyError>;
+}
+
+impl SecurityTxt {
+    /// Verify the signature of a signed file
+    pub fn verify<V>(&self, verifier: &V) -> Result<Fingerprint, VerifyError>
+    where
+        V: SignatureVerifier + ?Sized,
+    {
+        match self {
+            Self::Unsigned(_) => Err(VerifyError::NotSigned),
+            Self::Signed(text, _, signature) => {
+                let signature = signature
+                    .parse::<Armor>()
+                    .map_err(|error| VerifyError::InvalidArmor(error.to_string()))?;
+                if signature.kind != Kind::Signature {
+                    let detail = format!("expected a signature, found a {}", signature.kind);
+                    return Err(VerifyError::InvalidArmor(detail));
+                }
+                verifier.verify(cleartext::canonicalize(text).as_bytes(), &signature.data)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder;
+
+    impl SignatureVerifier for Recorder {
+        fn verify(&self, text: &[u8], signature: &[u8]) -> Result<Fingerprint, VerifyError> {
+            assert_eq!(
+                text,
+                b"Contact: mailto:a@b.com\r\n\r\nPolicy: https://b.com"
+            );
+            assert_eq!(signature, b"foo");
+            Ok(Fingerprint::new(vec![0xAB, 0x01]))
+        }
+    }
+
+    #[test]
+    fn verify_passes_canonical_text() {
+        let security_txt = SecurityTxt::Signed(
+            "Contact: mailto:a@b.com \n\t\nPolicy: https://b.com".into(),
+            Vec::new(),
+            "-----BEGIN PGP SIGNATURE-----\n\nZm9v\n-----END PGP SIGNATURE-----".into(),
+        );
+        let fingerprint = security_txt.verify(&Recorder).unwrap();
+        assert_eq!(fingerprint.to_string(), "AB01");
+        assert_eq!(Ok(fingerprint), "ab 01".parse());
+        assert_eq!(
+            SecurityTxt::Unsigned(Vec::new()).verify(&Recorder),
+            Err(VerifyError::NotSigned)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use crate::armor::Armor;
+use crate::{cleartext, Field, SecurityTxt};
+use chrono::SecondsFormat;
+use std::fmt;
+use std::io;
+
+/// The line ending to separate lines with when writing a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How to write a file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WriteOptions {
+    pub line_ending: LineEnding,
+    /// A comment to write before the fields, one `#` line per line of text
+    ///
+    /// This is ignored for signed files, since it would invalidate the signature.
+    pub header: Option<String>,
+}
+
+impl Field {
+    /// The canonical name of the field, or the name as written for extensions
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Extension(name, _) => name,
+            _ => self.kind().expect("only extensions have no kind").name(),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.name())?;
+        match self {
+            Self::Acknowledgments(url)
+            | Self::Canonical(url)
+            | Self::Encryption(url)
+            | Self::Hiring(url)
+            | Self::Policy(url) => write!(f, "{}", url),
+            Self::Contact(contact) => write!(f, "{}", contact),
+            Self::Expires(datetime) => {
+                write!(
+                    f,
+                    "{}",
+                    datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                )
+            }
+            Self::PreferredLanguages(languages) => {
+                for (i, language) in languages.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", language)?;
+                }
+                Ok(())
+            }
+            Self::Extension(_, value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl SecurityTxt {
+    fn render(&self, options: &WriteOptions) -> String {
+        let eol = options.line_ending.as_str();
+        let mut output = String::new();
+        match self {
+            Self::Unsigned(fields) => {
+                if let Some(header) = &options.header {
+                    for line in header.lines() {
+                        if line.is_empty() {
+                            output.push('#');
+                        } else {
+                            output.push_str("# ");
+                            output.push_str(line);
+                        }
+                        output.push_str(eol);
+                    }
+                }
+                for field in fields {
+                    output.push_str(&format!("{}{}", field, eol));
+                }
+            }
+            Self::Signed(text, _, signature) => {
+                output.push_str(cleartext::BEGIN_SIGNED_MESSAGE);
+                output.push_str(eol);
+                if let Some(hash) = signature
+                    .parse::<Armor>()
+                    .ok()
+                    .and_then(|armor| cleartext::hash_algorithm(&armor.data))
+                {
+                    output.push_str(&format!("Hash: {}{}", hash, eol));
+                }
+                output.push_str(eol);
+                for line in text.split('\n') {
+                    let line = line.strip_suffix('\r').unwrap_or(line);
+                    output.push_str(&cleartext::dash_escape(line));
+                    output.push_str(eol);
+                }
+                for line in signature.lines() {
+                    output.push_str(line);
+                    output.push_str(eol);
+                }
+            }
+        }
+        output
+    }
+
+    /// Write the file using the default `WriteOptions`
+    pub fn write_to(&self, writer: impl io::Write) -> io::Result<()> {
+        self.write_with(writer, &WriteOptions::default())
+    }
+
+    /// Write the file using the given options
+    pub fn write_with(&self, mut writer: impl io::Write, options: &WriteOptions) -> io::Result<()> {
+        writer.write_all(self.render(options).as_bytes())
+    }
+}
+
+impl fmt::Display for SecurityTxt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(&WriteOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn display_field() {
+        let lines = [
+            "Contact: mailto:security@example.com",
+            "Expires: 2021-12-31T18:37:07Z",
+            "Expires: 2021-12-31T18:37:07.500+01:00",
+            "Preferred-Languages: en, da",
+            "CSAF: https://example.com/provider-metadata.json",
+        ];
+        for line in lines.iter() {
+            assert_eq!(&Field::from_str(line).unwrap().to_string(), line);
+        }
+        assert_eq!(
+            Field::from_str("preferred-languages:en,fr")
+                .unwrap()
+                .to_string(),
+            "Preferred-Languages: en, fr"
+        );
+    }
+
+    #[test]
+    fn write_with_options() {
+        let security_txt = SecurityTxt::from_str("Contact: mailto:a@b.com").unwrap();
+        let options = WriteOptions {
+            line_ending: LineEnding::CrLf,
+            header: Some("Generated file\n\nDo not edit".into()),
+        };
+        let mut output = Vec::new();
+        security_txt.write_with(&mut output, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "# Generated file\r\n#\r\n# Do not edit\r\nContact: mailto:a@b.com\r\n"
+        );
+        assert_eq!(security_txt.to_string(), "Contact: mailto:a@b.com\n");
+    }
+}
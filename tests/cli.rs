@@ -0,0 +1,270 @@
+#![cfg(feature = "cli")]
+
+use security_txt::{parse, PgpVerifier};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_security-txt"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands that fail early may exit without reading their input
+    let written = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    if let Err(error) = written {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn lint_exit_code_follows_severity() {
+    let output = run(&["lint", "tests/files/basic.txt"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output)
+        .contains("tests/files/basic.txt: warning: line 4: contact is not a URI in Contact field"));
+
+    let signed = |now: &str| run(&["lint", "--now", now, "tests/files/signed.txt"], "");
+    let output = signed("2026-01-01T00:00:00Z");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "tests/files/signed.txt: warning[expires-too-far]: \
+         the Expires date should be less than a year into the future\n"
+    );
+    let output = signed("2030-06-01T00:00:00+02:00");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "tests/files/signed.txt: no problems found\n"
+    );
+    let output = signed("2031-01-01T00:00:00Z");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).contains("error[expires-past]"));
+
+    let output = run(
+        &["lint", "--spec", "draft-09", "-"],
+        "Contact: mailto:a@b.com\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "<stdin>: info[not-signed]: the file should be digitally signed\n"
+    );
+}
+
+#[test]
+fn lint_json() {
+    let output = run(
+        &[
+            "lint",
+            "--format=json",
+            "--url",
+            "https://b.com/security.txt",
+            "-",
+        ],
+        "Contact: a@b.com\nCanonical: https://a.com/security.txt\n",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stdout(&output),
+        concat!(
+            r#"{"source":"<stdin>","severity":"error","diagnostics":["#,
+            r#"{"severity":"warning","kind":"bare-contact","line":1,"span":[9,16],"#,
+            r#""field":"Contact","message":"contact is not a URI: interpreted as mailto:a@b.com"}],"#,
+            r#""findings":[{"severity":"error","rule":"expires-missing","field":null,"#,
+            r#""message":"the Expires field must be present"},"#,
+            r#"{"severity":"warning","rule":"canonical-mismatch","field":1,"#,
+            r#""message":"the file was retrieved from https://b.com/security.txt, "#,
+            r#"which is not a Canonical URI: https://a.com/security.txt"},"#,
+            r#"{"severity":"info","rule":"not-signed","field":null,"#,
+            r#""message":"the file should be digitally signed"}]}"#,
+            "\n"
+        )
+    );
+}
+
+#[test]
+fn scan() {
+    // Nothing listens on port 1, so every domain fails without any network
+    let domains = "# Domains\nlocalhost:1\n\n invalid domain \n";
+    let output = run(&["scan", "--format", "csv", "--interval=0", "-"], domains);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = stdout(&output);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        "domain,found,url,location,signed,expires,contacts,severity,diagnostics,findings,error"
+    );
+    let mut rows = lines[1..].to_vec();
+    rows.sort_unstable();
+    assert_eq!(
+        rows[0],
+        "invalid domain,false,,,false,,0,,0,,\"invalid domain \"\"invalid domain\"\"\""
+    );
+    assert!(rows[1].starts_with("localhost:1,false,,,false,,0,,0,,"));
+
+    let output = run(&["scan", "--concurrency", "1", "-"], "localhost:1\n");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(
+        r#"{"domain":"localhost:1","found":false,"url":null,"location":null,"signed":false,"#
+    ));
+    assert!(stdout.contains(r#""findings":[],"error":"#));
+
+    assert_eq!(
+        run(&["scan", "--concurrency", "0", "-"], "").status.code(),
+        Some(3)
+    );
+    assert_eq!(
+        run(&["scan", "--format", "xml", "-"], "").status.code(),
+        Some(3)
+    );
+}
+
+#[test]
+fn generate() {
+    let directory = std::env::temp_dir().join(format!("security-txt-cli-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let config = directory.join("config");
+    fs::write(
+        &config,
+        "# Example configuration\n\
+         header = Our security contact details\n\
+         contact = security@example.com\n\
+         contact = +44 5555 555 555\n\
+         expires = 2030-01-01T00:00:00Z\n\
+         preferred-languages = en, da\n\
+         extension = CSAF: https://example.com/provider-metadata.json\n",
+    )
+    .unwrap();
+
+    let output = run(&["generate", "--config", config.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "# Our security contact details\n\
+         Contact: mailto:security@example.com\n\
+         Contact: tel:+44-5555-555-555\n\
+         Expires: 2030-01-01T00:00:00Z\n\
+         Preferred-Languages: en, da\n\
+         CSAF: https://example.com/provider-metadata.json\n"
+    );
+
+    fs::write(&config, "expires = 2030-01-01T00:00:00Z\n").unwrap();
+    let output = run(&["generate", "--config", config.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(2));
+
+    fs::write(&config, "contacts = security@example.com\n").unwrap();
+    let output = run(&["generate", "--config", config.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("line 1: unknown key \"contacts\""));
+
+    let days = format!("contact = a@b.com\nexpires-in-days = {}\n", u64::MAX);
+    fs::write(&config, days).unwrap();
+    let output = run(&["generate", "--config", config.to_str().unwrap()], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("line 2: too many days"));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn renew() {
+    let directory = std::env::temp_dir().join(format!("security-txt-renew-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file = directory.join("security.txt");
+    let path = file.to_str().unwrap();
+    fs::write(
+        &file,
+        "# Kept as is\nContact: mailto:a@b.com\nExpires: 2000-01-01T00:00:00Z\n",
+    )
+    .unwrap();
+
+    let output = run(&["renew", path], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("the file expired at 2000-01-01"));
+
+    let output = run(&["renew", "--force", "--days", "30", path], "");
+    assert_eq!(output.status.code(), Some(0));
+    let renewed = fs::read_to_string(&file).unwrap();
+    assert!(renewed.starts_with("# Kept as is\nContact: mailto:a@b.com\nExpires: 20"));
+    assert!(!renewed.contains("2000-01-01"));
+
+    let output = run(&["renew", "-"], &renewed);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("# Kept as is\n"));
+
+    let signed = fs::read_to_string("tests/files/signed.txt").unwrap();
+    let output = run(&["renew", "--force", "-"], &signed);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn sign() {
+    let directory = std::env::temp_dir().join(format!("security-txt-sign-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let config = directory.join("config");
+    fs::write(
+        &config,
+        "header = Not signed\ncontact = security@example.com\nexpires-in-days = 90\n",
+    )
+    .unwrap();
+    let key = "tests/files/keys/example.sec.asc";
+    let verifier = PgpVerifier::from_file("tests/files/keys/example.pub.asc").unwrap();
+
+    let config = config.to_str().unwrap();
+    let output = run(&["generate", "--config", config, "--key", key], "");
+    assert_eq!(output.status.code(), Some(0));
+    let generated = stdout(&output);
+    assert!(!generated.contains("Not signed"));
+    assert!(parse(&generated).unwrap().verify(&verifier).is_ok());
+
+    let signed = fs::read_to_string("tests/files/signed.txt").unwrap();
+    // Forced, so that the fixture may have expired
+    let output = run(&["renew", "--force", "--key", key, "-"], &signed);
+    assert_eq!(output.status.code(), Some(0));
+    let renewed = stdout(&output);
+    assert!(!renewed.contains("Expires: 2030-12-31T23:59:59Z"));
+    assert!(parse(&renewed).unwrap().verify(&verifier).is_ok());
+
+    let output = run(
+        &["renew", "--key", "tests/files/keys/example.pub.asc", "-"],
+        &signed,
+    );
+    assert_eq!(output.status.code(), Some(3));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn usage_errors() {
+    assert_eq!(run(&[], "").status.code(), Some(3));
+    assert_eq!(run(&["lint"], "").status.code(), Some(3));
+    assert_eq!(
+        run(&["lint", "--bogus", "x", "-"], "").status.code(),
+        Some(3)
+    );
+    assert_eq!(run(&["lint", "missing.txt"], "").status.code(), Some(3));
+    assert_eq!(
+        run(&["lint", "--now", "2030-01-01", "-"], "").status.code(),
+        Some(3)
+    );
+    assert_eq!(
+        run(&["renew", "--force", "--force", "-"], "").status.code(),
+        Some(3)
+    );
+    assert_eq!(run(&["--help"], "").status.code(), Some(0));
+}
@@ -0,0 +1,134 @@
+#![allow(dead_code)]
+
+use rustls::pki_types::CertificateDer;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+pub const PLAIN: &str = "text/plain; charset=utf-8";
+
+/// A request received by the test server
+pub struct Request<'a> {
+    /// The port the server listens on
+    pub port: u16,
+    /// The `Host` header, without the port
+    pub host: &'a str,
+    pub path: &'a str,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+pub fn ok(content_type: &str, body: &str) -> Response {
+    Response {
+        status: 200,
+        headers: vec![("Content-Type", content_type.into())],
+        body: body.into(),
+    }
+}
+
+pub fn redirect(location: String) -> Response {
+    Response {
+        status: 301,
+        headers: vec![("Location", location)],
+        body: String::new(),
+    }
+}
+
+pub fn not_found() -> Response {
+    Response {
+        status: 404,
+        headers: vec![("Content-Type", "text/html".into())],
+        body: "<h1>Not Found</h1>".into(),
+    }
+}
+
+/// Serve HTTPS on localhost with a self-signed certificate, answering each
+/// request with `route`, and return the port and certificate
+pub fn serve<F>(route: F) -> (u16, CertificateDer<'static>)
+where
+    F: Fn(&Request) -> Response + Send + 'static,
+{
+    serve_names(&["localhost", "127.0.0.1"], route)
+}
+
+/// Like `serve`, with a certificate for the given names
+pub fn serve_names<F>(names: &[&str], route: F) -> (u16, CertificateDer<'static>)
+where
+    F: Fn(&Request) -> Response + Send + 'static,
+{
+    let names = names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let rcgen::CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(names).unwrap();
+    let server_config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(key_pair.serialize_der().into()),
+        )
+        .unwrap();
+    let server_config = Arc::new(server_config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let connection = rustls::ServerConnection::new(server_config.clone()).unwrap();
+            let mut stream = rustls::StreamOwned::new(connection, stream.unwrap());
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buffer[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request);
+            let path = match request.split(' ').nth(1) {
+                Some(path) => path,
+                None => continue,
+            };
+            let host = request
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("host"))
+                .map(|(_, value)| value.trim())
+                .unwrap_or_default();
+            let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+            let response = route(&Request { port, host, path });
+            let mut output = format!(
+                "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n",
+                response.status,
+                response.body.len()
+            );
+            for (name, value) in response.headers {
+                output.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            output.push_str("\r\n");
+            output.push_str(&response.body);
+            let _ = stream.write_all(output.as_bytes());
+            let _ = stream.flush();
+            stream.conn.send_close_notify();
+            let _ = stream.flush();
+        }
+    });
+
+    (port, cert.der().clone())
+}
+
+/// A client configuration that trusts only the certificate of the test server
+pub fn client_config(cert: CertificateDer<'static>) -> Arc<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(cert).unwrap();
+    Arc::new(
+        rustls::ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
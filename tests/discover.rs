@@ -0,0 +1,214 @@
+#![cfg(any(feature = "ureq", feature = "reqwest"))]
+
+mod common;
+
+use common::{not_found, ok, redirect, serve, PLAIN};
+#[cfg(feature = "ureq")]
+use rustls::pki_types::CertificateDer;
+#[cfg(feature = "ureq")]
+use security_txt::{discover_with, Discovery, Location, Rule, SpecVersion, UreqFetcher};
+use security_txt::{DiscoveryError, DiscoveryOptions};
+
+const BODY: &str = "Contact: mailto:security@example.com\nExpires: 2030-12-31T23:59:59Z\n";
+
+/// A fetcher that trusts the certificate of the test server
+#[cfg(feature = "ureq")]
+fn fetcher(cert: CertificateDer<'static>) -> UreqFetcher {
+    UreqFetcher::with_agent(
+        ureq::AgentBuilder::new()
+            .redirects(0)
+            .tls_config(common::client_config(cert))
+            .build(),
+    )
+}
+
+#[cfg(feature = "ureq")]
+fn discover(domain: &str, cert: CertificateDer<'static>) -> Result<Discovery, DiscoveryError> {
+    discover_with(domain, &DiscoveryOptions::default(), &fetcher(cert))
+}
+
+#[cfg(feature = "ureq")]
+fn rules(findings: &[security_txt::Finding]) -> Vec<Rule> {
+    findings.iter().map(|finding| finding.rule).collect()
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn well_known() {
+    let (port, cert) = serve(|request| match request.path {
+        "/.well-known/security.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let discovery = discover(&format!("localhost:{}", port), cert).unwrap();
+    assert_eq!(discovery.location, Location::WellKnown);
+    assert_eq!(
+        discovery.url.as_str(),
+        format!("https://localhost:{}/.well-known/security.txt", port)
+    );
+    assert!(discovery.redirects.is_empty());
+    assert!(discovery.findings.is_empty());
+    assert!(discovery.diagnostics.is_empty());
+    assert_eq!(discovery.security_txt.fields().len(), 2);
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn body_limit() {
+    let (port, cert) = serve(|request| match request.path {
+        "/.well-known/security.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let domain = format!("localhost:{}", port);
+    let options = DiscoveryOptions::default();
+    let fetcher = fetcher(cert).body_limit(BODY.len() as u64);
+    assert!(discover_with(&domain, &options, &fetcher).is_ok());
+    match discover_with(&domain, &options, &fetcher.body_limit(10)) {
+        Err(DiscoveryError::Transport(_, error)) => {
+            assert!(error.to_string().contains("larger than 10 bytes"))
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn legacy_path_depends_on_spec() {
+    let (port, cert) = serve(|request| match request.path {
+        "/security.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let domain = format!("localhost:{}", port);
+    match discover(&domain, cert.clone()) {
+        Err(DiscoveryError::NotFound(attempts)) => {
+            assert_eq!(attempts.len(), 1);
+            assert_eq!(attempts[0].0.path(), "/.well-known/security.txt");
+            assert_eq!(attempts[0].1, 404);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    let options = DiscoveryOptions {
+        spec: SpecVersion::Draft09,
+        ..DiscoveryOptions::default()
+    };
+    let discovery = discover_with(&domain, &options, &fetcher(cert)).unwrap();
+    assert_eq!(discovery.location, Location::Legacy);
+    assert_eq!(discovery.url.path(), "/security.txt");
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn redirects_are_reported() {
+    let (port, cert) = serve(|request| match request.path {
+        "/.well-known/security.txt" => redirect("/security.txt".into()),
+        "/security.txt" => redirect(format!("https://127.0.0.1:{}/moved.txt", request.port)),
+        "/moved.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let discovery = discover(&format!("localhost:{}", port), cert).unwrap();
+    assert_eq!(discovery.location, Location::WellKnown);
+    assert_eq!(
+        discovery.url.as_str(),
+        format!("https://127.0.0.1:{}/moved.txt", port)
+    );
+    assert_eq!(discovery.redirects.len(), 2);
+    assert!(!discovery.redirects[0].is_cross_host());
+    assert!(discovery.redirects[1].is_cross_host());
+    assert_eq!(
+        rules(&discovery.findings),
+        vec![Rule::Redirected, Rule::CrossHostRedirect]
+    );
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn insecure_redirect_is_refused() {
+    let (port, cert) = serve(|request| redirect(format!("http://localhost:{}/", request.port)));
+    match discover(&format!("localhost:{}", port), cert) {
+        Err(DiscoveryError::InsecureRedirect(url)) => assert_eq!(url.scheme(), "http"),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn redirect_loop() {
+    let (port, cert) = serve(|request| redirect(request.path.into()));
+    assert!(matches!(
+        discover(&format!("localhost:{}", port), cert),
+        Err(DiscoveryError::TooManyRedirects(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "ureq")]
+fn content_type_is_checked() {
+    let (port, cert) = serve(|_| ok("text/plain", BODY));
+    let discovery = discover(&format!("localhost:{}", port), cert).unwrap();
+    assert_eq!(discovery.content_type.as_deref(), Some("text/plain"));
+    assert_eq!(rules(&discovery.findings), vec![Rule::ContentTypeInvalid]);
+}
+
+#[cfg(feature = "reqwest")]
+fn reqwest_fetcher(cert: &[u8]) -> security_txt::ReqwestFetcher {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .add_root_certificate(reqwest::Certificate::from_der(cert).unwrap())
+        .build()
+        .unwrap();
+    security_txt::ReqwestFetcher::with_client(client)
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn reqwest() {
+    let (port, cert) = serve(|request| match request.path {
+        "/.well-known/security.txt" => redirect("/security.txt".into()),
+        "/security.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let discovery = security_txt::discover_async(
+        &format!("localhost:{}", port),
+        &DiscoveryOptions::default(),
+        &reqwest_fetcher(&cert),
+    )
+    .await
+    .unwrap();
+    assert_eq!(discovery.redirects.len(), 1);
+    assert_eq!(discovery.redirects[0].status, Some(301));
+    assert_eq!(discovery.url.path(), "/security.txt");
+    assert_eq!(discovery.content_type.as_deref(), Some(PLAIN));
+    assert_eq!(discovery.security_txt.fields().len(), 2);
+
+    match security_txt::discover_async(
+        "localhost:1",
+        &DiscoveryOptions::default(),
+        &security_txt::ReqwestFetcher::new(),
+    )
+    .await
+    {
+        Err(DiscoveryError::Transport(url, _)) => assert_eq!(url.port(), Some(1)),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn reqwest_body_limit() {
+    let (port, cert) = serve(|request| match request.path {
+        "/.well-known/security.txt" => ok(PLAIN, BODY),
+        _ => not_found(),
+    });
+    let domain = format!("localhost:{}", port);
+    let options = DiscoveryOptions::default();
+    let fetcher = reqwest_fetcher(&cert).body_limit(BODY.len() as u64);
+    assert!(security_txt::discover_async(&domain, &options, &fetcher)
+        .await
+        .is_ok());
+    match security_txt::discover_async(&domain, &options, &fetcher.body_limit(10)).await {
+        Err(DiscoveryError::Transport(_, error)) => {
+            assert!(error.to_string().contains("larger than 10 bytes"))
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,251 @@
+use security_txt::armor::{Armor, ArmorError, Kind};
+use security_txt::{
+    classify, parse, parse_with_diagnostics, ContactUri, Document, ErrorKind, Field,
+    NotSecurityTxt, SecurityTxt, Severity, SpecVersion,
+};
+use url::Url;
+
+fn url(string: &str) -> Url {
+    Url::parse(string).unwrap()
+}
+
+fn contact(string: &str) -> Field {
+    Field::Contact(string.parse().unwrap())
+}
+
+fn fields(security_txt: SecurityTxt) -> Vec<Field> {
+    match security_txt {
+        SecurityTxt::Unsigned(fields) => fields,
+        SecurityTxt::Signed(..) => panic!("expected an unsigned file"),
+    }
+}
+
+#[test]
+fn basic() {
+    let error = parse(include_str!("files/basic.txt")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidUrl);
+    assert_eq!(error.line(), 4);
+}
+
+#[test]
+fn basic_diagnostics() {
+    let (security_txt, diagnostics) = parse_with_diagnostics(include_str!("files/basic.txt"));
+    assert_eq!(
+        &security_txt.fields()[..4],
+        &[
+            Field::Contact(ContactUri::Email(url("mailto:mail@example.com"))),
+            Field::Contact(ContactUri::Web(url("https://example.com/security"))),
+            Field::Contact(ContactUri::Phone(url("tel:+44-5555-555-555"))),
+            Field::Encryption(url("https://example.com/pgpkey.txt")),
+        ]
+    );
+    let diagnostics: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.severity,
+                diagnostic.error.line(),
+                diagnostic.error.kind(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        diagnostics,
+        vec![
+            (Severity::Warning, 4, ErrorKind::BareContact),
+            (Severity::Warning, 6, ErrorKind::BareContact),
+            (Severity::Error, 7, ErrorKind::InvalidUrl),
+            (Severity::Error, 10, ErrorKind::InvalidUrl),
+            (Severity::Warning, 12, ErrorKind::DeprecatedName),
+            (Severity::Warning, 13, ErrorKind::DeprecatedName),
+            (Severity::Error, 13, ErrorKind::InvalidUrl),
+        ]
+    );
+    assert_eq!(
+        security_txt.fields()[4],
+        Field::Acknowledgments(url("https://example.com/hof"))
+    );
+}
+
+#[test]
+fn facebook() {
+    let fields = fields(parse(include_str!("files/facebook.com.txt")).unwrap());
+    assert_eq!(
+        fields,
+        vec![
+            contact("https://www.facebook.com/whitehat/report/"),
+            Field::Acknowledgments(url("https://www.facebook.com/whitehat/thanks/")),
+            Field::Policy(url("https://www.facebook.com/whitehat/info/")),
+            Field::Hiring(url("https://www.facebook.com/careers/teams/security/")),
+        ]
+    );
+}
+
+#[test]
+fn github() {
+    let fields = fields(parse(include_str!("files/github.com.txt")).unwrap());
+    assert_eq!(fields.len(), 5);
+    assert_eq!(fields[0], contact("https://hackerone.com/github"));
+    assert!(matches!(&fields[2], Field::PreferredLanguages(languages) if languages.len() == 1));
+    assert_eq!(
+        fields[3],
+        Field::Canonical(url("https://github.com/.well-known/security.txt"))
+    );
+}
+
+#[test]
+fn google() {
+    let fields = fields(parse(include_str!("files/google.com.txt")).unwrap());
+    assert_eq!(fields.len(), 6);
+    assert_eq!(fields[0], contact("https://g.co/vulnz"));
+    assert_eq!(fields[1], contact("mailto:security@google.com"));
+    assert_eq!(
+        fields[3],
+        Field::Extension(
+            "Acknowledgements".into(),
+            "https://bughunter.withgoogle.com/".into()
+        )
+    );
+
+    let (security_txt, diagnostics) = parse_with_diagnostics(include_str!("files/google.com.txt"));
+    assert_eq!(
+        security_txt.fields()[3],
+        Field::Acknowledgments(url("https://bughunter.withgoogle.com/"))
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].error.kind(), ErrorKind::DeprecatedName);
+}
+
+#[test]
+fn lobsters() {
+    let error = parse(include_str!("files/lobste.rs.txt")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::MissingColon);
+    // Served with a 200 status in place of a missing file
+    assert_eq!(
+        classify(include_str!("files/lobste.rs.txt"), Some("text/html")),
+        Err(NotSecurityTxt::HtmlDetected)
+    );
+}
+
+#[test]
+fn npmjs() {
+    // `Contact: security@npmjs.com` is not a valid URI
+    let error = parse(include_str!("files/npmjs.com.txt")).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidUrl);
+    assert_eq!(error.field(), Some("Contact"));
+
+    let (security_txt, diagnostics) = parse_with_diagnostics(include_str!("files/npmjs.com.txt"));
+    assert_eq!(
+        security_txt.fields()[0],
+        contact("mailto:security@npmjs.com")
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
+
+#[test]
+fn securitytxt_org() {
+    let fields = fields(parse(include_str!("files/securitytxt.org.txt")).unwrap());
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[0], contact("https://hackerone.com/ed"));
+    assert_eq!(
+        fields[1],
+        Field::Encryption(url("https://keybase.pub/edoverflow/pgp_key.asc"))
+    );
+
+    let (security_txt, _) = parse_with_diagnostics(include_str!("files/securitytxt.org.txt"));
+    assert_eq!(
+        security_txt.fields()[2],
+        Field::Acknowledgments(url("https://hackerone.com/ed/thanks"))
+    );
+}
+
+#[test]
+fn signed() {
+    let input = include_str!("files/signed.txt");
+    let (text, fields, signature) = match parse(input).unwrap() {
+        SecurityTxt::Signed(text, fields, signature) => (text, fields, signature),
+        SecurityTxt::Unsigned(_) => panic!("expected a signed file"),
+    };
+    assert!(text.starts_with("# Our security contact details\n"));
+    assert!(text.ends_with("\nPolicy: https://example.com/security-policy.html"));
+    assert!(input.contains(&text));
+    assert_eq!(fields.len(), 7);
+    assert_eq!(fields[0], contact("mailto:security@example.com"));
+    assert!(signature.starts_with("-----BEGIN PGP SIGNATURE-----\n"));
+    assert!(signature.ends_with("\n-----END PGP SIGNATURE-----"));
+    let armor: Armor = signature.parse().unwrap();
+    assert_eq!(armor.kind, Kind::Signature);
+    assert_eq!(armor.to_string(), signature);
+}
+
+#[test]
+fn armored_key() {
+    let input = include_str!("files/keys/example.pub.asc");
+    let armor: Armor = input.parse().unwrap();
+    assert_eq!(armor.kind, Kind::PublicKey);
+    // A version 4 public key packet, as `gpg --dearmor` shows
+    assert_eq!(armor.data[..3], [0x98, 0x33, 0x04]);
+    assert_eq!(armor.to_string().parse::<Armor>().unwrap(), armor);
+    let tampered = input.replacen("mDMEa", "mDMEb", 1);
+    assert!(matches!(
+        tampered.parse::<Armor>(),
+        Err(ArmorError::ChecksumMismatch { line: 8, .. })
+    ));
+}
+
+#[test]
+fn round_trip() {
+    let inputs = [
+        include_str!("files/facebook.com.txt"),
+        include_str!("files/github.com.txt"),
+        include_str!("files/google.com.txt"),
+        include_str!("files/securitytxt.org.txt"),
+        include_str!("files/signed.txt"),
+        include_str!("files/ycombinator.com.txt"),
+    ];
+    for input in inputs.iter() {
+        let security_txt = parse(input).unwrap();
+        let mut output = Vec::new();
+        security_txt.write_to(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(parse(&output).unwrap(), security_txt, "{}", output);
+    }
+}
+
+#[test]
+fn signed_output_is_unchanged() {
+    let input = include_str!("files/signed.txt");
+    assert_eq!(parse(input).unwrap().to_string(), input);
+}
+
+#[test]
+fn document_reprints_input() {
+    let inputs = [
+        include_str!("files/basic.txt"),
+        include_str!("files/facebook.com.txt"),
+        include_str!("files/github.com.txt"),
+        include_str!("files/google.com.txt"),
+        include_str!("files/lobste.rs.txt"),
+        include_str!("files/npmjs.com.txt"),
+        include_str!("files/securitytxt.org.txt"),
+        include_str!("files/signed.txt"),
+        include_str!("files/ycombinator.com.txt"),
+    ];
+    for input in inputs.iter() {
+        let document = Document::parse(input);
+        assert_eq!(document.to_string(), *input);
+        assert_eq!(
+            document.to_security_txt_with_diagnostics(SpecVersion::Draft09),
+            SecurityTxt::parse_with_diagnostics(input, SpecVersion::Draft09)
+        );
+        let crlf = input.replace('\n', "\r\n");
+        assert_eq!(Document::parse(&crlf).to_string(), crlf);
+    }
+}
+
+#[test]
+fn ycombinator() {
+    let fields = fields(parse(include_str!("files/ycombinator.com.txt")).unwrap());
+    assert!(fields.is_empty());
+}
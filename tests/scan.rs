@@ -0,0 +1,96 @@
+#![cfg(feature = "ureq")]
+
+mod common;
+
+use common::{not_found, ok, serve_names, PLAIN};
+use security_txt::{scan, DiscoveryError, Location, NotSecurityTxt, ScanOptions, UreqFetcher};
+use std::fs;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+const DOMAINS: &[&str] = &[
+    "facebook.com",
+    "github.com",
+    "google.com",
+    "lobste.rs",
+    "npmjs.com",
+    "securitytxt.org",
+    "ycombinator.com",
+    "example.com",
+    "missing.example",
+];
+
+/// Serve the files in tests/files as the security.txt files of their domains,
+/// with `signed.txt` as that of example.com, and return a fetcher that
+/// resolves every domain to the server
+fn fetcher() -> UreqFetcher {
+    let (port, cert) = serve_names(DOMAINS, |request| {
+        let name = match request.host {
+            "example.com" => "signed",
+            host => host,
+        };
+        match (
+            request.path,
+            fs::read_to_string(format!("tests/files/{}.txt", name)),
+        ) {
+            ("/.well-known/security.txt", Ok(body)) => ok(PLAIN, &body),
+            _ => not_found(),
+        }
+    });
+    UreqFetcher::with_agent(
+        ureq::AgentBuilder::new()
+            .redirects(0)
+            .tls_config(common::client_config(cert))
+            .resolver(move |_: &str| Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))]))
+            .build(),
+    )
+}
+
+#[test]
+fn fixtures() {
+    let options = ScanOptions {
+        concurrency: 4,
+        host_interval: Duration::ZERO,
+        ..ScanOptions::default()
+    };
+    let rows = scan(DOMAINS, &options, &fetcher());
+    let summary: Vec<_> = rows
+        .iter()
+        .map(|row| (&*row.domain, row.found(), row.signed, row.contacts))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("facebook.com", true, false, 1),
+            ("github.com", true, false, 1),
+            ("google.com", true, false, 2),
+            ("lobste.rs", false, false, 0),
+            ("npmjs.com", true, false, 1),
+            ("securitytxt.org", true, false, 1),
+            ("ycombinator.com", false, false, 0),
+            ("example.com", true, true, 2),
+            ("missing.example", false, false, 0),
+        ]
+    );
+    assert!(matches!(
+        rows[3].error,
+        Some(DiscoveryError::NotSecurityTxt(
+            _,
+            NotSecurityTxt::HtmlDetected
+        ))
+    ));
+    assert!(matches!(
+        rows[6].error,
+        Some(DiscoveryError::NotSecurityTxt(_, NotSecurityTxt::NoFields))
+    ));
+    assert!(matches!(rows[8].error, Some(DiscoveryError::NotFound(_))));
+
+    let example = &rows[7];
+    assert_eq!(example.location, Some(Location::WellKnown));
+    assert_eq!(
+        example.url.as_ref().map(|url| url.as_str()),
+        Some("https://example.com/.well-known/security.txt")
+    );
+    assert!(example.expires.is_some());
+    assert!(example.diagnostics.is_empty());
+}
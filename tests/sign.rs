@@ -0,0 +1,122 @@
+#![cfg(feature = "openpgp")]
+
+use security_txt::{
+    parse, renew_signed, Document, Fingerprint, PgpSigner, PgpVerifier, RenewPolicy, SecurityTxt,
+    SecurityTxtBuilder,
+};
+use std::fs;
+use std::process::Command;
+
+const FINGERPRINT: &str = "4A78DB3F72B26B9C205A16CA5F4A508355E96216";
+
+fn signer() -> PgpSigner {
+    PgpSigner::from_file("tests/files/keys/example.sec.asc").unwrap()
+}
+
+fn verifier() -> PgpVerifier {
+    PgpVerifier::from_file("tests/files/keys/example.pub.asc").unwrap()
+}
+
+/// Verify a file with GnuPG, if it is installed, using a fresh keyring with
+/// only the public key
+fn gpg_verify(name: &str, file: &str) -> Option<bool> {
+    let directory =
+        std::env::temp_dir().join(format!("security-txt-{}-{}", name, std::process::id()));
+    let home = directory.join("gnupg");
+    fs::create_dir_all(&home).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&home, fs::Permissions::from_mode(0o700)).unwrap();
+    }
+    let path = directory.join("security.txt");
+    fs::write(&path, file).unwrap();
+    let gpg = |args: &[&str]| {
+        Command::new("gpg")
+            .arg("--batch")
+            .arg("--homedir")
+            .arg(&home)
+            .args(args)
+            .output()
+    };
+    let verified = match gpg(&["--import", "tests/files/keys/example.pub.asc"]) {
+        Ok(output) => {
+            assert!(output.status.success(), "{:?}", output);
+            let output = gpg(&["--verify", path.to_str().unwrap()]).unwrap();
+            Some(output.status.success())
+        }
+        Err(_) => {
+            eprintln!("gpg is not installed, skipping");
+            None
+        }
+    };
+    let _ = fs::remove_dir_all(&directory);
+    verified
+}
+
+#[test]
+fn sign_generated_file() {
+    let signer = signer();
+    assert_eq!(signer.fingerprint().to_string(), FINGERPRINT);
+    let security_txt = SecurityTxtBuilder::new()
+        .contact_email("security@example.com")
+        .expires_in(std::time::Duration::from_secs(90 * 24 * 60 * 60))
+        .policy("https://example.com/security-policy.html")
+        .build()
+        .unwrap();
+    let signed = security_txt.sign(&signer).unwrap();
+    let output = signed.to_string();
+    assert!(output.starts_with("-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\n"));
+
+    let parsed = parse(&output).unwrap();
+    assert_eq!(parsed.fields(), security_txt.fields());
+    let fingerprint = parsed.verify(&verifier()).unwrap();
+    assert_eq!(fingerprint, FINGERPRINT.parse::<Fingerprint>().unwrap());
+    assert_ne!(gpg_verify("generated", &output), Some(false));
+
+    let tampered = output.replace("security-policy", "other-policy");
+    assert!(parse(&tampered).unwrap().verify(&verifier()).is_err());
+    assert_ne!(gpg_verify("tampered", &tampered), Some(true));
+}
+
+#[test]
+fn sign_document() {
+    // Dash-escaped lines, trailing whitespace and CRLF line endings
+    let input = "# Our security contact details  \r\n\
+                 -Dashed: value\r\n\
+                 Contact: mailto:security@example.com\t\r\n\
+                 Expires: 2030-12-31T23:59:59Z\r\n";
+    let mut document = Document::parse(input);
+    document.sign(&signer()).unwrap();
+    let output = document.to_string();
+    assert!(output.contains("\r\n- -Dashed: value\r\n"));
+    assert!(output.contains("mailto:security@example.com\t\r\n"));
+
+    let security_txt = parse(&output).unwrap();
+    assert!(security_txt.verify(&verifier()).is_ok());
+    assert_ne!(gpg_verify("document", &output), Some(false));
+}
+
+#[test]
+fn renew_signed_file() {
+    let mut document = Document::parse(include_str!("files/signed.txt"));
+    let policy = RenewPolicy {
+        now: "2030-06-01T00:00:00Z".parse().unwrap(),
+        ..RenewPolicy::default()
+    };
+    let expires = renew_signed(&mut document, &policy, &signer()).unwrap();
+    assert_eq!(expires.to_rfc3339(), "2030-11-28T00:00:00+00:00");
+    let output = document.to_string();
+    assert!(output.contains("\nExpires: 2030-11-28T00:00:00Z\n"));
+    assert!(matches!(parse(&output).unwrap(), SecurityTxt::Signed(..)));
+    assert!(parse(&output).unwrap().verify(&verifier()).is_ok());
+    assert_ne!(gpg_verify("renewed", &output), Some(false));
+}
+
+#[test]
+fn invalid_keys() {
+    let public = fs::read_to_string("tests/files/keys/example.pub.asc").unwrap();
+    assert!(PgpSigner::from_armored(&public).is_err());
+    assert!(PgpSigner::from_armored("not a key").is_err());
+    assert!(PgpSigner::from_file("tests/files/keys/missing.asc").is_err());
+}
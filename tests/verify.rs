@@ -0,0 +1,104 @@
+#![cfg(feature = "openpgp")]
+
+#[cfg(feature = "discover")]
+use security_txt::MockFetcher;
+use security_txt::{parse, Fingerprint, PgpVerifier, SecurityTxt, VerifyError};
+use std::collections::HashMap;
+use std::fs;
+use url::Url;
+
+const FINGERPRINT: &str = "4A78DB3F72B26B9C205A16CA5F4A508355E96216";
+
+fn verifier() -> PgpVerifier {
+    PgpVerifier::from_file("tests/files/keys/example.pub.asc").unwrap()
+}
+
+const ENCRYPTION: &str = "https://example.com/.well-known/pgp-key.txt";
+
+fn key(name: &str) -> String {
+    fs::read_to_string(format!("tests/files/keys/{}.pub.asc", name)).unwrap()
+}
+
+#[test]
+fn signed_by_local_key() {
+    let security_txt = parse(include_str!("files/signed.txt")).unwrap();
+    let verifier = verifier();
+    let fingerprint = security_txt.verify(&verifier).unwrap();
+    assert_eq!(fingerprint, FINGERPRINT.parse::<Fingerprint>().unwrap());
+    assert_eq!(
+        verifier.fingerprints().collect::<Vec<_>>(),
+        vec![fingerprint]
+    );
+}
+
+#[test]
+fn signed_by_encryption_key() {
+    let security_txt = parse(include_str!("files/signed.txt")).unwrap();
+    let published = |key: String| {
+        let mut keys = HashMap::new();
+        keys.insert(Url::parse(ENCRYPTION).unwrap(), key);
+        security_txt.verify_published(&keys)
+    };
+    assert_eq!(published(key("example")), Ok(FINGERPRINT.parse().unwrap()));
+    // A different key at the Encryption URL did not make the signature
+    assert_eq!(published(key("other")), Err(VerifyError::UnknownSigner));
+
+    // The right key, but not at the Encryption URL
+    let mut keys = HashMap::new();
+    keys.insert(
+        Url::parse("https://example.com/other-key.txt").unwrap(),
+        key("example"),
+    );
+    assert_eq!(
+        security_txt.verify_published(&keys),
+        Err(VerifyError::NoPublishedKey)
+    );
+}
+
+#[cfg(feature = "discover")]
+#[test]
+fn signed_by_fetched_encryption_key() {
+    let security_txt = parse(include_str!("files/signed.txt")).unwrap();
+    let fetcher = MockFetcher::new().with_body(ENCRYPTION, "text/plain", key("example"));
+    assert_eq!(
+        security_txt.verify_published_with(&fetcher),
+        Ok(FINGERPRINT.parse().unwrap())
+    );
+    assert_eq!(fetcher.requests(), vec![Url::parse(ENCRYPTION).unwrap()]);
+
+    let fetcher = MockFetcher::new().with_body(ENCRYPTION, "text/plain", key("other"));
+    assert_eq!(
+        security_txt.verify_published_with(&fetcher),
+        Err(VerifyError::UnknownSigner)
+    );
+    assert!(matches!(
+        security_txt.verify_published_with(&MockFetcher::new()),
+        Err(VerifyError::InvalidKey(_))
+    ));
+}
+
+#[test]
+fn tampered() {
+    let input = include_str!("files/signed.txt").replace("Expires: 2030", "Expires: 2031");
+    let security_txt = parse(&input).unwrap();
+    assert_eq!(
+        security_txt.verify(&verifier()),
+        Err(VerifyError::BadSignature)
+    );
+}
+
+#[test]
+fn trailing_whitespace_is_not_signed() {
+    let input = include_str!("files/signed.txt").replace("\nPolicy:", " \t\r\nPolicy:");
+    let security_txt = parse(&input).unwrap();
+    assert!(security_txt.verify(&verifier()).is_ok());
+}
+
+#[test]
+fn unsigned() {
+    let security_txt = SecurityTxt::Unsigned(Vec::new());
+    assert_eq!(
+        security_txt.verify(&verifier()),
+        Err(VerifyError::NotSigned)
+    );
+}